@@ -0,0 +1,65 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::{state::Ticket, Vec};
+use pda_pinocchio_mapping::{mapping, MappingContext};
+
+/// Writes `amount` into the tickets `(payer, 0..n)`, one per passed account.
+///
+/// Data: `[mode, amount (u64 LE), bump_0, .., bump_n-1]`, where `mode` is
+/// - 0: every entry rebuilds its mapping and reads the Rent sysvar,
+/// - 1: a single `MappingContext` serves all entries,
+/// - 2: all entries are written by one `set_many` call.
+pub fn process_airdrop_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Airdrop instruction");
+
+    let [payer, system_program, tickets_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    if data.len() < 9 || data.len() - 9 != tickets_accounts.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let mode = data[0];
+    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let bumps = &data[9..];
+
+    let ticket = |bump: u8| Ticket {
+        amount: amount.into(),
+        bump,
+    };
+
+    match mode {
+        0 => {
+            for (index, (account, &bump)) in tickets_accounts.iter().zip(bumps).enumerate() {
+                let tickets = mapping!(Ticket::MAPPING_NAME, payer => Ticket);
+                tickets.set(&(payer.key(), index as u8), ticket(bump), account)?;
+            }
+        }
+        1 => {
+            let ctx =
+                MappingContext::new(&crate::ID, payer)?.with_system_program(system_program)?;
+            let tickets = ctx.mapping::<Ticket>(Ticket::MAPPING_NAME);
+
+            for (index, (account, &bump)) in tickets_accounts.iter().zip(bumps).enumerate() {
+                tickets.set(&(payer.key(), index as u8), ticket(bump), account)?;
+            }
+        }
+        2 => {
+            let entries = bumps
+                .iter()
+                .enumerate()
+                .map(|(index, &bump)| ((payer.key(), index as u8), ticket(bump)))
+                .collect::<Vec<_>>();
+
+            let tickets = mapping!(Ticket::MAPPING_NAME, payer => Ticket);
+            if let Err(e) = tickets.set_many(&entries, tickets_accounts) {
+                log!("Airdrop failed at ticket {}", e.index);
+                return Err(e.into());
+            }
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::double_mapping;
+
+const ALLOWANCES: &[u8] = b"allowances";
+
+/// Sets or reads the amount `owner` lets `spender` move, a two-level
+/// mapping entry derived from `[allowances, owner, spender, bump]`.
+///
+/// Data: `[mode, allowance_bump, ..]`, where `mode` is
+/// - 0: set the allowance to `[amount (u64 LE)]`, `owner` must sign,
+/// - 1: log the current allowance.
+pub fn process_allowance_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Allowance instruction");
+
+    let [owner, spender, allowance_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, allowance_bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let allowances = double_mapping!(ALLOWANCES, owner => Ticket).with_header();
+
+    match (mode, args.len()) {
+        (0, 8) => {
+            if !owner.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            let allowance = Ticket {
+                amount: u64::from_le_bytes(args.try_into().unwrap()).into(),
+                bump: *allowance_bump,
+            };
+            allowances.set(owner.key(), spender.key(), allowance, allowance_account)
+        }
+        (1, 0) => {
+            let allowance = allowances.get(
+                owner.key(),
+                spender.key(),
+                *allowance_bump,
+                allowance_account,
+            )?;
+            log!("Allowance {}", allowance.amount.get());
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -0,0 +1,48 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use crate::state::{Badge, BadgeV1};
+use pda_pinocchio_mapping::mapping;
+
+const BADGES: &[u8] = b"badges";
+
+/// Awards a badge to `user`, or upgrades it to the current layout.
+///
+/// Data: `[mode, badge_bump, ..]`, where `mode` is
+/// - 0: award a `BadgeV1` of `[points (u64 LE)]`, as older versions of the
+///   program did,
+/// - 1: migrate the badge to `Badge`, its level derived from the points.
+pub fn process_badge_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Badge instruction");
+
+    let [user, badge_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, badge_bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Badges are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    match (mode, args.len()) {
+        (0, 8) => {
+            let badge = BadgeV1 {
+                points: u64::from_le_bytes(args.try_into().unwrap()).into(),
+                bump: *badge_bump,
+            };
+            mapping!(BADGES, user => BadgeV1)
+                .with_header()
+                .create(user.key(), badge, badge_account)
+        }
+        (1, 0) => mapping!(BADGES, user => Badge)
+            .with_header()
+            .migrate::<BadgeV1>(user.key(), *badge_bump, badge_account, |old| Badge {
+                points: old.points,
+                level: ((old.points.get() / 100) as u32).into(),
+                bump: old.bump,
+            }),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
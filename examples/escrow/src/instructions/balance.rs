@@ -0,0 +1,61 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use pda_pinocchio_mapping::{mapping, PodU64};
+
+const BALANCES: &[u8] = b"balances";
+
+/// Keeps a plain `u64` balance of `user`. The value carries no bump: the
+/// bump lives in the entry header, found on-chain when the entry is created.
+///
+/// Data: `[mode, ..]`, where `mode` is
+/// - 0: create the balance with `[amount (u64 LE)]`,
+/// - 1: set it to `[amount (u64 LE)]`, creating it if needed,
+/// - 2: overwrite it with `[amount (u64 LE)]`,
+/// - 3: add `[amount (u64 LE)]` in place,
+/// - 4: log it,
+/// - 5: close it, refunding its rent to `user`.
+pub fn process_balance_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Balance instruction");
+
+    let [user, balance_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Balances are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let balances = mapping!(BALANCES, user => PodU64).with_bump_header();
+    let amount = || -> Result<u64, ProgramError> {
+        Ok(u64::from_le_bytes(
+            args.try_into()
+                .map_err(|_| ProgramError::InvalidInstructionData)?,
+        ))
+    };
+
+    match mode {
+        0 => balances.create_entry(user.key(), None, amount()?.into(), balance_account),
+        1 => balances.set_entry(user.key(), None, amount()?.into(), balance_account),
+        2 => balances.update_entry(user.key(), amount()?.into(), balance_account),
+        3 => {
+            let amount = amount()?;
+            let mut balance = balances.get_entry_mut(user.key(), balance_account)?;
+            *balance = balance
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok(())
+        }
+        4 => {
+            let balance = balances.get_entry(user.key(), balance_account)?;
+            log!("Balance {}", balance.get());
+            Ok(())
+        }
+        5 => balances.remove_entry(user.key(), balance_account, user),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use crate::state::Share;
+
+pub fn process_cancel_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Cancel instruction");
+
+    let [taker, maker, shares_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    let shares_bump = *data.first().ok_or(ProgramError::InvalidInstructionData)?;
+
+    // Only the taker recorded in the share may close it
+    {
+        let shares = Share::mapping(&crate::ID, taker);
+        let shares_state = shares.get(maker.key(), shares_bump, shares_account)?;
+        if !taker.is_signer() || shares_state.taker != *taker.key() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+
+    let shares = Share::mapping(&crate::ID, taker);
+    shares.remove(maker.key(), shares_bump, shares_account, taker)?;
+
+    Ok(())
+}
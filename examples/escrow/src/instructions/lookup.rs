@@ -0,0 +1,30 @@
+use pinocchio::{
+    account_info::AccountInfo, msg, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+use pinocchio_log::log;
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::ForeignMapping;
+
+/// Reads the ticket of `owner` kept by another program, which stores its
+/// tickets as a header-less `tickets` mapping.
+///
+/// Data: `[ticket_bump, program_id (32 bytes)]`.
+pub fn process_lookup_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Lookup instruction");
+
+    let [owner, ticket_account, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (&ticket_bump, program_id) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let program_id: &Pubkey = program_id
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let tickets = ForeignMapping::<Ticket>::new(program_id, Ticket::MAPPING_NAME);
+    let ticket = tickets.get(owner.key(), ticket_bump, ticket_account)?;
+    log!("Foreign ticket {}", ticket.amount.get());
+    Ok(())
+}
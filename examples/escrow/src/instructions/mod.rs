@@ -1,9 +1,37 @@
+pub mod airdrop;
+pub mod allowance;
+pub mod badge;
+pub mod balance;
+pub mod cancel;
+pub mod lookup;
 pub mod make;
+pub mod note;
+pub mod payout;
+pub mod points;
+pub mod position;
+pub mod profile;
+pub mod quote;
+pub mod redeem;
 pub mod take;
+pub mod vault;
 // pub mod make_2;
 
+pub use airdrop::*;
+pub use allowance::*;
+pub use badge::*;
+pub use balance::*;
+pub use cancel::*;
+pub use lookup::*;
 pub use make::*;
+pub use note::*;
+pub use payout::*;
+pub use points::*;
+pub use position::*;
+pub use profile::*;
+pub use quote::*;
+pub use redeem::*;
 pub use take::*;
+pub use vault::*;
 // pub use make_2::*;
 
 pub enum EscrowInstrctions {
@@ -11,6 +39,20 @@ pub enum EscrowInstrctions {
     Take = 1,
     Cancel = 2,
     MakeV2 = 3,
+    Airdrop = 4,
+    Deposit = 5,
+    Withdraw = 6,
+    Note = 7,
+    Payout = 8,
+    Redeem = 9,
+    Quote = 10,
+    Allowance = 11,
+    Badge = 12,
+    Balance = 13,
+    Position = 14,
+    Lookup = 15,
+    Profile = 16,
+    Points = 17,
 }
 
 impl TryFrom<&u8> for EscrowInstrctions {
@@ -22,6 +64,20 @@ impl TryFrom<&u8> for EscrowInstrctions {
             1 => Ok(EscrowInstrctions::Take),
             2 => Ok(EscrowInstrctions::Cancel),
             3 => Ok(EscrowInstrctions::MakeV2),
+            4 => Ok(EscrowInstrctions::Airdrop),
+            5 => Ok(EscrowInstrctions::Deposit),
+            6 => Ok(EscrowInstrctions::Withdraw),
+            7 => Ok(EscrowInstrctions::Note),
+            8 => Ok(EscrowInstrctions::Payout),
+            9 => Ok(EscrowInstrctions::Redeem),
+            10 => Ok(EscrowInstrctions::Quote),
+            11 => Ok(EscrowInstrctions::Allowance),
+            12 => Ok(EscrowInstrctions::Badge),
+            13 => Ok(EscrowInstrctions::Balance),
+            14 => Ok(EscrowInstrctions::Position),
+            15 => Ok(EscrowInstrctions::Lookup),
+            16 => Ok(EscrowInstrctions::Profile),
+            17 => Ok(EscrowInstrctions::Points),
             _ => Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
         }
     }
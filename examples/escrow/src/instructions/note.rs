@@ -0,0 +1,27 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use pda_pinocchio_mapping::mapping;
+
+const NOTES: &[u8] = b"notes";
+
+/// Writes a free-form note of `user`, resizing its account to the note length.
+///
+/// Data: `[note_bump, note bytes..]`.
+pub fn process_note_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Note instruction");
+
+    let [user, note_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (&note_bump, note) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    // Notes are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let notes = mapping!(NOTES, user => [u8]);
+    notes.set_slice(user.key(), note_bump, note, note_account)
+}
@@ -0,0 +1,57 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use pda_pinocchio_mapping::{mapping, TokenTransfer};
+
+const PAYOUTS: &[u8] = b"payouts";
+
+/// Pays out of the `payouts` PDA of `user`, signing as that PDA.
+///
+/// Data: `[mode, payout_bump, amount (u64 LE), ..]`, where `mode` is
+/// - 0: lamports, accounts `[user, payout_account, to, system_program]`,
+/// - 1: tokens, with one more data byte `decimals` and accounts
+///   `[user, payout_account, from, mint, to, token_program]`.
+pub fn process_payout_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Payout instruction");
+
+    if data.len() < 10 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (mode, payout_bump) = (data[0], data[1]);
+    let amount = u64::from_le_bytes(data[2..10].try_into().unwrap());
+
+    let [user, payout_account, rest @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+
+    // Payouts are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let payouts = mapping!(PAYOUTS, user => [u8]);
+    match (mode, rest) {
+        (0, [to, _system_program @ ..]) => {
+            payouts.transfer_lamports(user.key(), payout_bump, payout_account, to, amount)
+        }
+        (1, [from, mint, to, token_program, ..]) => {
+            let &[decimals] = &data[10..] else {
+                return Err(ProgramError::InvalidInstructionData);
+            };
+            payouts.transfer_tokens(
+                user.key(),
+                payout_bump,
+                TokenTransfer {
+                    token_program,
+                    from,
+                    mint,
+                    to,
+                    authority: payout_account,
+                    amount,
+                    decimals,
+                },
+            )
+        }
+        (0 | 1, _) => Err(ProgramError::NotEnoughAccountKeys),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
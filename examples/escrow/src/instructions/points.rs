@@ -0,0 +1,59 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::mapping;
+
+const POINTS: &[u8] = b"points";
+
+/// Loyalty points of `user`, read as zero until the first award.
+///
+/// Data: `[mode, points_bump, ..]`, where `mode` is
+/// - 0: log the points, without creating anything,
+/// - 1: award `[amount (u64 LE)]` points, creating the entry if needed,
+/// - 2: same as 1, but with a default value holding the wrong bump.
+pub fn process_points_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Points instruction");
+
+    let [user, points_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, points_bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Points are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let points = mapping!(POINTS, user => Ticket);
+
+    match (mode, args.len()) {
+        (0, 0) => {
+            let current = points.get_or_default(user.key(), *points_bump, points_account)?;
+            log!("Points {}", current.amount.get());
+            Ok(())
+        }
+        (1 | 2, 8) => {
+            let amount = u64::from_le_bytes(args.try_into().unwrap());
+            let default_bump = if *mode == 1 {
+                *points_bump
+            } else {
+                points_bump.wrapping_sub(1)
+            };
+
+            let mut current =
+                points.get_or_create(user.key(), *points_bump, points_account, |_| Ticket {
+                    amount: 0.into(),
+                    bump: default_bump,
+                })?;
+            current.amount = current
+                .amount
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
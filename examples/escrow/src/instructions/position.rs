@@ -0,0 +1,51 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::mapping;
+
+const POSITIONS: &[u8] = b"positions";
+
+/// Opens the position of `user`, one per user: the mapping is strict, so
+/// only the canonical bump is accepted.
+///
+/// Data: `[mode, position_bump, ..]`, where `mode` is
+/// - 0: set the position to `[amount (u64 LE)]`,
+/// - 1: find the canonical PDA on-chain, check it is `position_account`
+///   and log its bump.
+pub fn process_position_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Position instruction");
+
+    let [user, position_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, position_bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Positions are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let positions = mapping!(POSITIONS, user => Ticket).strict();
+
+    match (mode, args.len()) {
+        (0, 8) => {
+            let position = Ticket {
+                amount: u64::from_le_bytes(args.try_into().unwrap()).into(),
+                bump: *position_bump,
+            };
+            positions.set(user.key(), position, position_account)
+        }
+        (1, 0) => {
+            let (address, bump) = positions.find(user.key())?;
+            if address != *position_account.key() {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            log!("Canonical bump {}", bump);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
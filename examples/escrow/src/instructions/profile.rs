@@ -0,0 +1,67 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::state::{Nickname, Profile};
+use pda_pinocchio_mapping::mapping;
+
+const PROFILES: &[u8] = b"profiles";
+const NICKNAMES: &[u8] = b"nicknames";
+
+/// Keeps the Borsh-serialized profile and nickname of `user`.
+///
+/// Data: `[mode, bump, ..]`, where `mode` is
+/// - 0, 1, 2: set, create or update the profile, with an optional
+///   `[referrer (32 bytes)]`,
+/// - 3: log whether the profile has a referrer,
+/// - 4: set the nickname to the remaining bytes.
+pub fn process_profile_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Profile instruction");
+
+    let [user, entry_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Profiles are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let profiles = mapping!(PROFILES, user => Profile);
+    let profile = || -> Result<Profile, ProgramError> {
+        let referrer = match args.len() {
+            0 => None,
+            32 => Some(args.try_into().unwrap()),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+        Ok(Profile {
+            referrer,
+            bump: *bump,
+        })
+    };
+
+    match mode {
+        0 => profiles.set_serialized(user.key(), &profile()?, entry_account),
+        1 => profiles.create_serialized(user.key(), &profile()?, entry_account),
+        2 => profiles.update_serialized(user.key(), &profile()?, entry_account),
+        3 => {
+            let profile = profiles.get_deserialized(user.key(), *bump, entry_account)?;
+            log!("Profile referred {}", profile.referrer.is_some() as u8);
+            Ok(())
+        }
+        4 => {
+            let nickname = Nickname {
+                nickname: args.to_vec(),
+                bump: *bump,
+            };
+            mapping!(NICKNAMES, user => Nickname).set_serialized(
+                user.key(),
+                &nickname,
+                entry_account,
+            )
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
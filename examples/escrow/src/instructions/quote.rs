@@ -0,0 +1,56 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+use pinocchio_log::log;
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::mapping;
+
+const QUOTES: &[u8] = b"quotes";
+
+/// Publishes the quote of `owner`, an amount with a write counter, so that
+/// relayers only overwrite the version they read.
+///
+/// Data: `[mode, quote_bump, ..]`, where `mode` is
+/// - 0: set the quote to `[amount (u64 LE)]`,
+/// - 1: update it to `[amount (u64 LE), expected_version (u64 LE)]`,
+///   failing if it was written since,
+/// - 2: log its current version.
+pub fn process_quote_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Quote instruction");
+
+    let [owner, quote_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let [mode, quote_bump, args @ ..] = data else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    // Quotes are keyed by their owner, who must sign
+    if !owner.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let quotes = mapping!(QUOTES, owner => Ticket).with_write_counter();
+    let quote = |amount: &[u8]| Ticket {
+        amount: u64::from_le_bytes(amount.try_into().unwrap()).into(),
+        bump: *quote_bump,
+    };
+
+    match (mode, args.len()) {
+        (0, 8) => quotes.set(owner.key(), quote(args), quote_account),
+        (1, 16) => {
+            let expected_version = u64::from_le_bytes(args[8..].try_into().unwrap());
+            quotes.update_versioned(
+                owner.key(),
+                quote(&args[..8]),
+                quote_account,
+                expected_version,
+            )
+        }
+        (2, 0) => {
+            let version = quotes.get_version(owner.key(), *quote_bump, quote_account)?;
+            log!("Quote version {}", version);
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -0,0 +1,61 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use crate::state::Ticket;
+use pda_pinocchio_mapping::mapping;
+
+/// Spends from the ticket `(owner, index)` written by Airdrop.
+///
+/// Data: `[mode, index, bump, amount (u64 LE), ..]`, where `mode` is
+/// - 0: redeem `amount` through `modify`, failing if the ticket holds less,
+/// - 1: compare-and-swap through `update_if`: a ticket of `amount` becomes
+///   `[new_amount (u64 LE), new_bump]`,
+/// - 2: redeem `amount` and move the ticket to `[new_bump]`, which `modify`
+///   must reject.
+pub fn process_redeem_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Redeem instruction");
+
+    let [owner, ticket_account, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if data.len() < 11 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (mode, index, bump) = (data[0], data[1], data[2]);
+    let amount = u64::from_le_bytes(data[3..11].try_into().unwrap());
+
+    // Tickets are keyed by their owner, who must sign
+    if !owner.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let tickets = mapping!(Ticket::MAPPING_NAME, owner => Ticket);
+    let key = (owner.key(), index);
+    let redeem = |ticket: &mut Ticket| -> ProgramResult {
+        ticket.amount = ticket
+            .amount
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    };
+
+    match (mode, &data[11..]) {
+        (0, []) => tickets.modify(&key, bump, ticket_account, redeem),
+        (1, [new_amount @ .., new_bump]) if new_amount.len() == 8 => {
+            let expected = Ticket {
+                amount: amount.into(),
+                bump,
+            };
+            let new = Ticket {
+                amount: u64::from_le_bytes(new_amount.try_into().unwrap()).into(),
+                bump: *new_bump,
+            };
+            tickets.update_if(&key, expected, new, ticket_account)
+        }
+        (2, &[new_bump]) => tickets.modify(&key, bump, ticket_account, |ticket| {
+            redeem(ticket)?;
+            ticket.bump = new_bump;
+            Ok(())
+        }),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -1,7 +1,6 @@
 use pinocchio::{account_info::AccountInfo, msg, ProgramResult};
 
 use crate::state::Share;
-use pda_pinocchio_mapping::mapping;
 
 pub fn process_take_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
     msg!("Processing Take instruction");
@@ -20,11 +19,11 @@ pub fn process_take_instruction(accounts: &[AccountInfo], data: &[u8]) -> Progra
     let shares_state = Share {
         maker: *maker.key(),
         taker: *taker.key(),
-        amount: amount_to_give.to_le_bytes(),
+        amount: amount_to_give.into(),
         bump: shares_bump,
     };
 
-    let shares = mapping!(b"shares", taker);
+    let shares = Share::mapping(&crate::ID, taker);
     shares.set(maker.key(), shares_state, shares_account)?;
 
     {
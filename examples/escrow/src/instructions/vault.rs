@@ -0,0 +1,42 @@
+use pinocchio::{account_info::AccountInfo, msg, program_error::ProgramError, ProgramResult};
+
+use pda_pinocchio_mapping::VaultMapping;
+
+const VAULTS: &[u8] = b"vaults";
+
+/// Data: `[vault_bump, amount (u64 LE)]`, shared by Deposit and Withdraw.
+fn parse_vault_data(data: &[u8]) -> Result<(u8, u64), ProgramError> {
+    if data.len() != 9 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    Ok((data[0], u64::from_le_bytes(data[1..9].try_into().unwrap())))
+}
+
+pub fn process_deposit_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Deposit instruction");
+
+    let [user, vault_account, _system_program @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (vault_bump, amount) = parse_vault_data(data)?;
+
+    let vaults = VaultMapping::new(&crate::ID, VAULTS, user);
+    vaults.deposit(user.key(), vault_bump, vault_account, user, amount)
+}
+
+pub fn process_withdraw_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    msg!("Processing Withdraw instruction");
+
+    let [user, vault_account, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (vault_bump, amount) = parse_vault_data(data)?;
+
+    // Vaults are keyed by their owner, who must sign
+    if !user.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let vaults = VaultMapping::new(&crate::ID, VAULTS, user);
+    vaults.withdraw(user.key(), vault_bump, vault_account, user, amount)
+}
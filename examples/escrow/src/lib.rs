@@ -40,6 +40,23 @@ pub fn process_instruction(
     match EscrowInstrctions::try_from(discriminator)? {
         EscrowInstrctions::Make => instructions::process_make_instruction(accounts, data)?,
         EscrowInstrctions::Take => instructions::process_take_instruction(accounts, data)?,
+        EscrowInstrctions::Cancel => instructions::process_cancel_instruction(accounts, data)?,
+        EscrowInstrctions::Airdrop => instructions::process_airdrop_instruction(accounts, data)?,
+        EscrowInstrctions::Deposit => instructions::process_deposit_instruction(accounts, data)?,
+        EscrowInstrctions::Withdraw => instructions::process_withdraw_instruction(accounts, data)?,
+        EscrowInstrctions::Note => instructions::process_note_instruction(accounts, data)?,
+        EscrowInstrctions::Payout => instructions::process_payout_instruction(accounts, data)?,
+        EscrowInstrctions::Redeem => instructions::process_redeem_instruction(accounts, data)?,
+        EscrowInstrctions::Quote => instructions::process_quote_instruction(accounts, data)?,
+        EscrowInstrctions::Allowance => {
+            instructions::process_allowance_instruction(accounts, data)?
+        }
+        EscrowInstrctions::Badge => instructions::process_badge_instruction(accounts, data)?,
+        EscrowInstrctions::Balance => instructions::process_balance_instruction(accounts, data)?,
+        EscrowInstrctions::Position => instructions::process_position_instruction(accounts, data)?,
+        EscrowInstrctions::Lookup => instructions::process_lookup_instruction(accounts, data)?,
+        EscrowInstrctions::Profile => instructions::process_profile_instruction(accounts, data)?,
+        EscrowInstrctions::Points => instructions::process_points_instruction(accounts, data)?,
         _ => return Err(pinocchio::program_error::ProgramError::InvalidInstructionData),
     }
     Ok(())
@@ -0,0 +1,29 @@
+use bytemuck::{Pod, Zeroable};
+use pda_pinocchio_mapping::{discriminator, Bumpy, MappingValue, PodU32, PodU64};
+
+/// First layout of a badge, kept around to migrate the entries written with it.
+#[repr(C)]
+#[derive(Clone, Copy, Bumpy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct BadgeV1 {
+    pub points: PodU64,
+    pub bump: u8,
+}
+
+impl MappingValue for BadgeV1 {
+    const DISCRIMINATOR: [u8; 8] = discriminator("Badge");
+    const VERSION: u8 = 1;
+}
+
+/// Current layout of a badge: adds the level reached.
+#[repr(C)]
+#[derive(Clone, Copy, Bumpy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Badge {
+    pub points: PodU64,
+    pub level: PodU32,
+    pub bump: u8,
+}
+
+impl MappingValue for Badge {
+    const DISCRIMINATOR: [u8; 8] = discriminator("Badge");
+    const VERSION: u8 = 2;
+}
@@ -1,6 +1,12 @@
+pub mod badge;
 pub mod escrow;
+pub mod profile;
 pub mod shares;
+pub mod ticket;
 
+pub use badge::*;
 pub use escrow::*;
+pub use profile::*;
 
 pub use shares::*;
+pub use ticket::*;
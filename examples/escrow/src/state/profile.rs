@@ -0,0 +1,28 @@
+use alloc::vec::Vec;
+use borsh::{BorshDeserialize, BorshSerialize};
+use pda_pinocchio_mapping::{BorshValue, Bumpy};
+
+/// Borsh-serialized value: its account is resized to the serialized length,
+/// 2 bytes without referrer, 34 with one.
+#[derive(BorshSerialize, BorshDeserialize, Bumpy, Debug, PartialEq)]
+pub struct Profile {
+    pub referrer: Option<[u8; 32]>,
+    pub bump: u8,
+}
+
+impl BorshValue for Profile {}
+
+/// Borsh-serialized value stored in accounts of a fixed length.
+#[derive(BorshSerialize, BorshDeserialize, Bumpy, Debug, PartialEq)]
+pub struct Nickname {
+    pub nickname: Vec<u8>,
+    pub bump: u8,
+}
+
+impl Nickname {
+    pub const MAX_NICKNAME_LEN: usize = 16;
+}
+
+impl BorshValue for Nickname {
+    const MAX_LEN: Option<usize> = Some(4 + Self::MAX_NICKNAME_LEN + 1);
+}
@@ -1,17 +1,10 @@
-use bytemuck::{Pod, Zeroable};
-use pda_pinocchio_mapping::Bumpy;
+use pda_pinocchio_mapping::{mapping_value, Bumpy, PodU64};
 
-impl Bumpy for Share {
-    fn bump(&self) -> u8 {
-        self.bump
-    }
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq, Zeroable, Pod)]
+#[mapping_value(name = "shares")]
+#[derive(Bumpy, Debug, Default, PartialEq)]
 pub struct Share {
     pub maker: [u8; 32],
     pub taker: [u8; 32],
-    pub amount: [u8; 8],
+    pub amount: PodU64,
     pub bump: u8,
 }
@@ -0,0 +1,8 @@
+use pda_pinocchio_mapping::{mapping_value, Bumpy, PodU64};
+
+#[mapping_value(name = "tickets")]
+#[derive(Bumpy, Debug, Default, PartialEq)]
+pub struct Ticket {
+    pub amount: PodU64,
+    pub bump: u8,
+}
@@ -1,10 +1,11 @@
 #[cfg(test)]
 mod tests {
-    use crate::state::Share;
+    use crate::state::{Badge, BadgeV1, Nickname, Share, Ticket};
+    use pda_pinocchio_mapping::{MappingError, MappingValue, HEADER_LEN};
     use std::path::PathBuf;
 
     use litesvm::LiteSVM;
-    use solana_instruction::{AccountMeta, Instruction};
+    use solana_instruction::{error::InstructionError, AccountMeta, Instruction};
     use solana_keypair::Keypair;
     use solana_message::Message;
     use solana_native_token::LAMPORTS_PER_SOL;
@@ -12,6 +13,7 @@ mod tests {
     use solana_pubkey::Pubkey;
     use solana_signer::Signer;
     use solana_transaction::Transaction;
+    use solana_transaction_error::TransactionError;
 
     const INITIAL_BALANCE: u64 = 10 * LAMPORTS_PER_SOL;
 
@@ -96,7 +98,7 @@ mod tests {
 
         // Derive the PDA for the escrow account using the maker's public key and a seed value
         let shares = Pubkey::find_program_address(
-            &[b"shares".as_ref(), payer.pubkey().as_ref()],
+            &[Share::MAPPING_NAME, payer.pubkey().as_ref()],
             &program_id,
         );
         msg!("shares PDA: {}\n", shares.0);
@@ -211,7 +213,1491 @@ mod tests {
             .expect("Could not retrieve account properly")
             .data;
 
-        let shares_ref: &Share = bytemuck::from_bytes(&shares_bytes);
-        msg!("amount is {}", u64::from_le_bytes(shares_ref.amount));
+        let shares_ref: &Share = bytemuck::from_bytes(&shares_bytes[HEADER_LEN..]);
+        msg!("amount is {}", shares_ref.amount.get());
+    }
+
+    /// Amounts exchanged by the Take instructions of the tests below.
+    const AMOUNT_TO_RECEIVE: u64 = 2_000_000_000; // 2 SOL with 9 decimal places
+    const AMOUNT_TO_GIVE: u64 = 1_000_000_000; // 1 SOL with 9 decimal places
+
+    fn shares_pda(maker: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Share::MAPPING_NAME, maker.as_ref()], &program_id())
+    }
+
+    fn escrow_pda(maker: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"escrow".as_ref(), maker.as_ref()], &program_id())
+    }
+
+    /// Take of `maker`'s escrow by `taker`, writing the shares entry at
+    /// `shares.0` with bump `shares.1`. Take does not read the escrow state,
+    /// its address is only forwarded.
+    fn take_ix(
+        maker: &Pubkey,
+        taker: &Pubkey,
+        shares: (Pubkey, u8),
+        escrow: &Pubkey,
+    ) -> Instruction {
+        let take_ix_data = [
+            vec![1u8], // Discriminator for "Take" instruction
+            shares.1.to_le_bytes().to_vec(),
+            AMOUNT_TO_RECEIVE.to_le_bytes().to_vec(), // Amount received by maker from taker
+            AMOUNT_TO_GIVE.to_le_bytes().to_vec(),    // Amount given by maker to taker
+        ]
+        .concat();
+
+        Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(*taker, true),
+                AccountMeta::new(*maker, false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new(shares.0, false),
+                AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+                AccountMeta::new(Rent::id(), false),
+            ],
+            data: take_ix_data,
+        }
+    }
+
+    fn send_take(
+        svm: &mut LiteSVM,
+        taker: &Keypair,
+        take_ix: Instruction,
+    ) -> litesvm::types::TransactionResult {
+        let message = Message::new(&[take_ix], Some(&taker.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[taker], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_cancel_instruction() {
+        let (mut svm, payer, taker) = setup();
+
+        let shares = shares_pda(&payer.pubkey());
+        let escrow = escrow_pda(&payer.pubkey());
+
+        // Take, so that the shares entry exists
+        let take_ix = take_ix(&payer.pubkey(), &taker.pubkey(), shares, &escrow.0);
+        send_take(&mut svm, &taker, take_ix).unwrap();
+
+        let shares_rent = svm
+            .get_account(&shares.0)
+            .expect("Could not retrieve account properly")
+            .lamports;
+        let taker_balance_before_cancel = svm
+            .get_account(&taker.pubkey())
+            .expect("Could not retrieve account properly")
+            .lamports;
+
+        // Cancel
+        let cancel_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(taker.pubkey(), true),
+                AccountMeta::new(payer.pubkey(), false),
+                AccountMeta::new(shares.0, false),
+                AccountMeta::new(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: vec![2u8, shares.1], // Discriminator for "Cancel" instruction
+        };
+
+        // Payer covers the fee so that the taker balance only reflects the refund
+        let message = Message::new(&[cancel_ix], Some(&payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&payer, &taker], message, recent_blockhash);
+        let tx = svm.send_transaction(transaction).unwrap();
+
+        msg!("\n\nCancel transaction sucessfull");
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // POSTCONDITIONS
+        let taker_account = svm
+            .get_account(&taker.pubkey())
+            .expect("Could not retrieve account properly");
+        assert_eq!(
+            taker_account.lamports,
+            taker_balance_before_cancel + shares_rent
+        );
+
+        let closed = svm.get_account(&shares.0);
+        assert!(closed.is_none_or(|account| account.lamports == 0 && account.data.is_empty()));
+    }
+
+    #[test]
+    pub fn test_cancel_without_bump() {
+        let (mut svm, payer, taker) = setup();
+
+        let shares = shares_pda(&payer.pubkey());
+
+        let cancel_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(taker.pubkey(), true),
+                AccountMeta::new(payer.pubkey(), false),
+                AccountMeta::new(shares.0, false),
+            ],
+            data: vec![2u8], // Discriminator for "Cancel" instruction, no bump
+        };
+
+        let message = Message::new(&[cancel_ix], Some(&taker.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&taker], message, recent_blockhash);
+
+        let failed = svm.send_transaction(transaction).unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    pub fn test_take_with_wrong_shares_account() {
+        let (mut svm, payer, taker) = setup();
+
+        let shares = shares_pda(&payer.pubkey());
+        let escrow = escrow_pda(&payer.pubkey());
+
+        // Escrow PDA passed where the shares PDA is expected
+        let take_ix = take_ix(
+            &payer.pubkey(),
+            &taker.pubkey(),
+            (escrow.0, shares.1),
+            &escrow.0,
+        );
+        let failed = send_take(&mut svm, &taker, take_ix).unwrap_err();
+
+        let TransactionError::InstructionError(_, InstructionError::Custom(code)) = failed.err
+        else {
+            panic!("Expected a custom program error, got {:?}", failed.err);
+        };
+        msg!("Take failed with: {}", MappingError::describe(code));
+        assert_eq!(MappingError::try_from(code), Ok(MappingError::PdaMismatch));
+    }
+
+    #[test]
+    pub fn test_take_with_prefunded_shares_account() {
+        let (mut svm, payer, taker) = setup();
+
+        let shares = shares_pda(&payer.pubkey());
+        let escrow = escrow_pda(&payer.pubkey());
+
+        // `Share::mapping()` stores a header in front of the value
+        let shares_len = HEADER_LEN + core::mem::size_of::<Share>();
+        let shares_rent = svm.minimum_balance_for_rent_exemption(shares_len);
+
+        // Griefing: the shares PDA already holds the rent of an empty account
+        let prefund = svm.minimum_balance_for_rent_exemption(0);
+        svm.airdrop(&shares.0, prefund).expect("Airdrop failed");
+
+        let take_ix = take_ix(&payer.pubkey(), &taker.pubkey(), shares, &escrow.0);
+        let tx = send_take(&mut svm, &taker, take_ix).unwrap();
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // POSTCONDITIONS
+        let shares_account = svm
+            .get_account(&shares.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(shares_account.owner, program_id());
+        assert_eq!(shares_account.data.len(), shares_len);
+        assert_eq!(shares_account.lamports, shares_rent);
+
+        assert_eq!(shares_account.data[..8], Share::DISCRIMINATOR);
+        let shares_ref: &Share = bytemuck::from_bytes(&shares_account.data[HEADER_LEN..]);
+        assert_eq!(shares_ref.taker, taker.pubkey().to_bytes());
+        assert_eq!(shares_ref.amount.get(), AMOUNT_TO_GIVE);
+    }
+
+    #[test]
+    pub fn test_take_with_overfunded_shares_account() {
+        let (mut svm, payer, taker) = setup();
+
+        let shares = shares_pda(&payer.pubkey());
+        let escrow = escrow_pda(&payer.pubkey());
+
+        // `Share::mapping()` stores a header in front of the value
+        let shares_len = HEADER_LEN + core::mem::size_of::<Share>();
+        let shares_rent = svm.minimum_balance_for_rent_exemption(shares_len);
+
+        // The shares PDA already holds more than the rent it needs
+        let prefund = shares_rent + 1_000;
+        svm.airdrop(&shares.0, prefund).expect("Airdrop failed");
+
+        let take_ix = take_ix(&payer.pubkey(), &taker.pubkey(), shares, &escrow.0);
+        let tx = send_take(&mut svm, &taker, take_ix).unwrap();
+        msg!("CUs Consumed: {}", tx.compute_units_consumed);
+
+        // POSTCONDITIONS
+        let shares_account = svm
+            .get_account(&shares.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(shares_account.owner, program_id());
+        assert_eq!(shares_account.data.len(), shares_len);
+        assert_eq!(shares_account.lamports, prefund);
+
+        assert_eq!(shares_account.data[..8], Share::DISCRIMINATOR);
+        let shares_ref: &Share = bytemuck::from_bytes(&shares_account.data[HEADER_LEN..]);
+        assert_eq!(shares_ref.taker, taker.pubkey().to_bytes());
+        assert_eq!(shares_ref.amount.get(), AMOUNT_TO_GIVE);
+    }
+
+    fn ticket_pda(owner: &Pubkey, index: u8) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Ticket::MAPPING_NAME, owner.as_ref(), &[index]],
+            &program_id(),
+        )
+    }
+
+    fn airdrop_ix(payer: &Pubkey, mode: u8, amount: u64, tickets: &[(Pubkey, u8)]) -> Instruction {
+        let airdrop_data = [
+            vec![4u8, mode], // Discriminator for "Airdrop" instruction
+            amount.to_le_bytes().to_vec(),
+            tickets.iter().map(|ticket| ticket.1).collect(),
+        ]
+        .concat();
+        let mut accounts = vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+        ];
+        accounts.extend(
+            tickets
+                .iter()
+                .map(|ticket| AccountMeta::new(ticket.0, false)),
+        );
+
+        Instruction {
+            program_id: program_id(),
+            accounts,
+            data: airdrop_data,
+        }
+    }
+
+    /// Sends an Airdrop writing `amount` into `count` tickets of `payer`,
+    /// returns the compute units consumed.
+    fn send_airdrop(svm: &mut LiteSVM, payer: &Keypair, mode: u8, count: u8, amount: u64) -> u64 {
+        let tickets = (0..count)
+            .map(|index| ticket_pda(&payer.pubkey(), index))
+            .collect::<Vec<_>>();
+        let airdrop_ix = airdrop_ix(&payer.pubkey(), mode, amount, &tickets);
+
+        let message = Message::new(&[airdrop_ix], Some(&payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[payer], message, recent_blockhash);
+        let tx = svm.send_transaction(transaction).unwrap();
+
+        for ticket in &tickets {
+            let ticket_account = svm
+                .get_account(&ticket.0)
+                .expect("Could not retrieve account properly");
+            let ticket_ref: &Ticket = bytemuck::from_bytes(&ticket_account.data);
+            assert_eq!(ticket_ref.amount.get(), amount);
+        }
+
+        tx.compute_units_consumed
+    }
+
+    #[test]
+    pub fn test_airdrop_context_saves_compute_units() {
+        let (mut svm, payer, taker) = setup();
+
+        let count = 10;
+        let plain = send_airdrop(&mut svm, &payer, 0, count, 1_000);
+        let cached = send_airdrop(&mut svm, &taker, 1, count, 1_000);
+
+        msg!(
+            "Airdrop of {} tickets, mapping per entry: {} CUs",
+            count,
+            plain
+        );
+        msg!(
+            "Airdrop of {} tickets, MappingContext: {} CUs",
+            count,
+            cached
+        );
+        assert!(cached < plain);
+
+        // Overwrites go through the same handles, nothing is created
+        let plain = send_airdrop(&mut svm, &payer, 0, count, 2_000);
+        let cached = send_airdrop(&mut svm, &taker, 1, count, 2_000);
+        msg!(
+            "Overwrite of {} tickets: {} CUs / {} CUs",
+            count,
+            plain,
+            cached
+        );
+    }
+
+    #[test]
+    pub fn test_airdrop_set_many() {
+        let (mut svm, payer, _) = setup();
+
+        // Creates 20 tickets in one instruction, then overwrites them
+        let tx_cus = send_airdrop(&mut svm, &payer, 2, 20, 1_000);
+        msg!("set_many of 20 tickets: {} CUs", tx_cus);
+        let tx_cus = send_airdrop(&mut svm, &payer, 2, 20, 2_000);
+        msg!("set_many overwrite of 20 tickets: {} CUs", tx_cus);
+    }
+
+    #[test]
+    pub fn test_airdrop_set_many_reports_failing_entry() {
+        let (mut svm, payer, _) = setup();
+
+        let mut tickets = (0..20)
+            .map(|index| ticket_pda(&payer.pubkey(), index))
+            .collect::<Vec<_>>();
+        // Ticket 7 gets the account of ticket 8
+        tickets[7].0 = tickets[8].0;
+
+        let airdrop_ix = airdrop_ix(&payer.pubkey(), 2, 1_000, &tickets);
+        let message = Message::new(&[airdrop_ix], Some(&payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[&payer], message, recent_blockhash);
+
+        let failed = svm.send_transaction(transaction).unwrap_err();
+
+        let TransactionError::InstructionError(_, InstructionError::Custom(code)) = failed.err
+        else {
+            panic!("Expected a custom program error, got {:?}", failed.err);
+        };
+        assert_eq!(MappingError::try_from(code), Ok(MappingError::PdaMismatch));
+        assert!(failed
+            .meta
+            .logs
+            .iter()
+            .any(|log| log.contains("Airdrop failed at ticket 7")));
+
+        // Nothing written before the failing entry is kept
+        assert!(svm
+            .get_account(&tickets[0].0)
+            .is_none_or(|account| account.data.is_empty()));
+    }
+
+    fn send_vault_ix(
+        svm: &mut LiteSVM,
+        user: &Keypair,
+        discriminator: u8,
+        vault: (Pubkey, u8),
+        amount: u64,
+    ) -> litesvm::types::TransactionResult {
+        let vault_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(vault.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![discriminator, vault.1], amount.to_le_bytes().to_vec()].concat(),
+        };
+
+        let message = Message::new(&[vault_ix], Some(&user.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_vault_deposit_and_withdraw() {
+        let (mut svm, payer, _) = setup();
+
+        let vault = Pubkey::find_program_address(
+            &[b"vaults".as_ref(), payer.pubkey().as_ref()],
+            &program_id(),
+        );
+        let vault_rent = svm.minimum_balance_for_rent_exemption(0);
+
+        // Deposit creates the vault
+        send_vault_ix(&mut svm, &payer, 5, vault, LAMPORTS_PER_SOL).unwrap();
+        let vault_account = svm
+            .get_account(&vault.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(vault_account.owner, program_id());
+        assert!(vault_account.data.is_empty());
+        assert_eq!(vault_account.lamports, vault_rent + LAMPORTS_PER_SOL);
+
+        // Second deposit tops it up
+        send_vault_ix(&mut svm, &payer, 5, vault, LAMPORTS_PER_SOL).unwrap();
+
+        let user_balance = svm
+            .get_account(&payer.pubkey())
+            .expect("Could not retrieve account properly")
+            .lamports;
+        let tx = send_vault_ix(&mut svm, &payer, 6, vault, LAMPORTS_PER_SOL / 2).unwrap();
+        msg!("Withdraw CUs Consumed: {}", tx.compute_units_consumed);
+
+        let vault_account = svm
+            .get_account(&vault.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(
+            vault_account.lamports,
+            vault_rent + 2 * LAMPORTS_PER_SOL - LAMPORTS_PER_SOL / 2
+        );
+        let user_account = svm
+            .get_account(&payer.pubkey())
+            .expect("Could not retrieve account properly");
+        assert!(user_account.lamports > user_balance);
+
+        // The rent-exempt minimum cannot be withdrawn
+        let balance = 3 * LAMPORTS_PER_SOL / 2;
+        let failed = send_vault_ix(&mut svm, &payer, 6, vault, balance + 1).unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+        );
+    }
+
+    fn lamports(svm: &LiteSVM, address: &Pubkey) -> u64 {
+        svm.get_account(address)
+            .expect("Could not retrieve account properly")
+            .lamports
+    }
+
+    /// Sends a Note of `user`, with `fee_payer` paying the transaction fee so
+    /// that the balance of `user` only moves by the note rent.
+    fn send_note(
+        svm: &mut LiteSVM,
+        fee_payer: &Keypair,
+        user: &Keypair,
+        note_pda: (Pubkey, u8),
+        note: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let note_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(note_pda.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![7u8, note_pda.1], note.to_vec()].concat(), // Discriminator for "Note" instruction
+        };
+
+        let message = Message::new(&[note_ix], Some(&fee_payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[fee_payer, user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_note_resize_keeps_rent_exempt_minimum() {
+        let (mut svm, payer, user) = setup();
+
+        let note_pda = Pubkey::find_program_address(
+            &[b"notes".as_ref(), user.pubkey().as_ref()],
+            &program_id(),
+        );
+
+        // Creation funds the rent of 10 bytes
+        let balance = lamports(&svm, &user.pubkey());
+        send_note(&mut svm, &payer, &user, note_pda, &[1; 10]).unwrap();
+        let small_rent = svm.minimum_balance_for_rent_exemption(10);
+        assert_eq!(lamports(&svm, &note_pda.0), small_rent);
+        assert_eq!(lamports(&svm, &user.pubkey()), balance - small_rent);
+
+        // Growing tops up the missing rent from the payer
+        let balance = lamports(&svm, &user.pubkey());
+        send_note(&mut svm, &payer, &user, note_pda, &[2; 1_000]).unwrap();
+        let large_rent = svm.minimum_balance_for_rent_exemption(1_000);
+        let note_account = svm
+            .get_account(&note_pda.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(note_account.data, vec![2; 1_000]);
+        assert_eq!(note_account.lamports, large_rent);
+        assert_eq!(
+            lamports(&svm, &user.pubkey()),
+            balance - (large_rent - small_rent)
+        );
+
+        // Shrinking refunds the excess to the payer
+        let balance = lamports(&svm, &user.pubkey());
+        send_note(&mut svm, &payer, &user, note_pda, &[3; 5]).unwrap();
+        let tiny_rent = svm.minimum_balance_for_rent_exemption(5);
+        let note_account = svm
+            .get_account(&note_pda.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(note_account.data, vec![3; 5]);
+        assert_eq!(note_account.lamports, tiny_rent);
+        assert_eq!(
+            lamports(&svm, &user.pubkey()),
+            balance + (large_rent - tiny_rent)
+        );
+
+        // Same length, nothing moves
+        let balance = lamports(&svm, &user.pubkey());
+        send_note(&mut svm, &payer, &user, note_pda, &[4; 5]).unwrap();
+        assert_eq!(lamports(&svm, &note_pda.0), tiny_rent);
+        assert_eq!(lamports(&svm, &user.pubkey()), balance);
+    }
+
+    const TOKEN_PROGRAM_ID: Pubkey =
+        solana_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+    fn payout_pda(user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"payouts".as_ref(), user.as_ref()], &program_id())
+    }
+
+    fn send_payout(
+        svm: &mut LiteSVM,
+        user: &Keypair,
+        mode: u8,
+        data: &[u8],
+        accounts: Vec<AccountMeta>,
+    ) -> litesvm::types::TransactionResult {
+        let payout = payout_pda(&user.pubkey());
+        let payout_ix = Instruction {
+            program_id: program_id(),
+            accounts: [
+                vec![
+                    AccountMeta::new(user.pubkey(), true),
+                    AccountMeta::new(payout.0, false),
+                ],
+                accounts,
+            ]
+            .concat(),
+            data: [vec![8u8, mode, payout.1], data.to_vec()].concat(), // Discriminator for "Payout" instruction
+        };
+
+        let message = Message::new(&[payout_ix], Some(&user.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    /// Mint of `token_program` with `decimals`, in the base 82-byte layout.
+    fn set_mint(svm: &mut LiteSVM, token_program: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; 82];
+        data[36..44].copy_from_slice(&1_000_000u64.to_le_bytes()); // supply
+        data[44] = decimals;
+        data[45] = 1; // is_initialized
+
+        svm.set_account(
+            mint,
+            solana_account::Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: *token_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        mint
+    }
+
+    /// Token account of `owner` holding `amount`, in the base 165-byte layout.
+    fn set_token_account(
+        svm: &mut LiteSVM,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let token_account = Pubkey::new_unique();
+        let mut data = vec![0u8; 165];
+        data[0..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1; // state: initialized
+
+        svm.set_account(
+            token_account,
+            solana_account::Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: *token_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+        token_account
+    }
+
+    fn token_amount(svm: &LiteSVM, token_account: &Pubkey) -> u64 {
+        let account = svm
+            .get_account(token_account)
+            .expect("Could not retrieve account properly");
+        u64::from_le_bytes(account.data[64..72].try_into().unwrap())
+    }
+
+    #[test]
+    pub fn test_payout_lamports_from_system_owned_entry() {
+        let (mut svm, user, _) = setup();
+        let payout = payout_pda(&user.pubkey());
+        let recipient = Pubkey::new_unique();
+
+        // A plain SOL holder, never created by the program
+        svm.airdrop(&payout.0, 2 * LAMPORTS_PER_SOL).unwrap();
+
+        send_payout(
+            &mut svm,
+            &user,
+            0,
+            &LAMPORTS_PER_SOL.to_le_bytes(),
+            vec![
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(lamports(&svm, &payout.0), LAMPORTS_PER_SOL);
+        assert_eq!(lamports(&svm, &recipient), LAMPORTS_PER_SOL);
+        assert_eq!(
+            svm.get_account(&payout.0).unwrap().owner,
+            solana_sdk_ids::system_program::ID
+        );
+    }
+
+    fn token_accounts(
+        from: Pubkey,
+        mint: Pubkey,
+        to: Pubkey,
+        token_program: Pubkey,
+    ) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(from, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(to, false),
+            AccountMeta::new_readonly(token_program, false),
+        ]
+    }
+
+    #[test]
+    pub fn test_payout_tokens() {
+        for token_program in [TOKEN_PROGRAM_ID, spl_token_2022::ID] {
+            let (mut svm, user, _) = setup();
+            let payout = payout_pda(&user.pubkey());
+
+            let mint = set_mint(&mut svm, &token_program, 6);
+            let from = set_token_account(&mut svm, &token_program, &mint, &payout.0, 1_000);
+            let to = set_token_account(&mut svm, &token_program, &mint, &Pubkey::new_unique(), 0);
+
+            let data = [400u64.to_le_bytes().as_ref(), &[6]].concat();
+            send_payout(
+                &mut svm,
+                &user,
+                1,
+                &data,
+                token_accounts(from, mint, to, token_program),
+            )
+            .unwrap();
+
+            assert_eq!(token_amount(&svm, &from), 600);
+            assert_eq!(token_amount(&svm, &to), 400);
+
+            // TransferChecked carries the decimals, the token program checks them
+            let data = [400u64.to_le_bytes().as_ref(), &[9]].concat();
+            let failed = send_payout(
+                &mut svm,
+                &user,
+                1,
+                &data,
+                token_accounts(from, mint, to, token_program),
+            )
+            .unwrap_err();
+            assert_eq!(
+                failed.err,
+                TransactionError::InstructionError(
+                    0,
+                    InstructionError::Custom(
+                        spl_token_2022::error::TokenError::MintDecimalsMismatch as u32
+                    )
+                )
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_payout_tokens_rejects_other_programs() {
+        let (mut svm, user, _) = setup();
+        let payout = payout_pda(&user.pubkey());
+
+        let mint = set_mint(&mut svm, &TOKEN_PROGRAM_ID, 6);
+        let from = set_token_account(&mut svm, &TOKEN_PROGRAM_ID, &mint, &payout.0, 1_000);
+        let to = set_token_account(&mut svm, &TOKEN_PROGRAM_ID, &mint, &Pubkey::new_unique(), 0);
+
+        // The PDA never signs for a program other than SPL Token / Token-2022
+        let data = [400u64.to_le_bytes().as_ref(), &[6]].concat();
+        let failed = send_payout(
+            &mut svm,
+            &user,
+            1,
+            &data,
+            token_accounts(from, mint, to, solana_sdk_ids::system_program::ID),
+        )
+        .unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+        );
+        assert_eq!(token_amount(&svm, &from), 1_000);
+    }
+
+    fn send_redeem(
+        svm: &mut LiteSVM,
+        owner: &Keypair,
+        ticket: (Pubkey, u8),
+        mode: u8,
+        amount: u64,
+        extra: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let redeem_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(ticket.0, false),
+            ],
+            // Discriminator for "Redeem" instruction, ticket index 0
+            data: [
+                vec![9u8, mode, 0, ticket.1],
+                amount.to_le_bytes().to_vec(),
+                extra.to_vec(),
+            ]
+            .concat(),
+        };
+
+        let message = Message::new(&[redeem_ix], Some(&owner.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[owner], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    fn account_data(svm: &LiteSVM, address: &Pubkey) -> Vec<u8> {
+        svm.get_account(address)
+            .expect("Could not retrieve account properly")
+            .data
+    }
+
+    fn assert_mapping_error(
+        failed: litesvm::types::FailedTransactionMetadata,
+        error: MappingError,
+    ) {
+        let TransactionError::InstructionError(0, InstructionError::Custom(code)) = failed.err
+        else {
+            panic!("Expected a custom program error, got {:?}", failed.err);
+        };
+        assert_eq!(MappingError::try_from(code), Ok(error));
+    }
+
+    #[test]
+    pub fn test_redeem_modify() {
+        let (mut svm, payer, _) = setup();
+        send_airdrop(&mut svm, &payer, 0, 1, 1_000);
+        let ticket = ticket_pda(&payer.pubkey(), 0);
+
+        send_redeem(&mut svm, &payer, ticket, 0, 400, &[]).unwrap();
+        let ticket_ref: Ticket = bytemuck::pod_read_unaligned(&account_data(&svm, &ticket.0));
+        assert_eq!(ticket_ref.amount.get(), 600);
+
+        // The closure fails: nothing is written
+        let before = account_data(&svm, &ticket.0);
+        let failed = send_redeem(&mut svm, &payer, ticket, 0, 700, &[]).unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InsufficientFunds)
+        );
+        assert_eq!(account_data(&svm, &ticket.0), before);
+
+        // The closure changes the bump: rejected, nothing is written
+        let failed = send_redeem(
+            &mut svm,
+            &payer,
+            ticket,
+            2,
+            100,
+            &[ticket.1.wrapping_sub(1)],
+        )
+        .unwrap_err();
+        assert_mapping_error(failed, MappingError::PdaMismatch);
+        assert_eq!(account_data(&svm, &ticket.0), before);
+    }
+
+    #[test]
+    pub fn test_redeem_update_if() {
+        let (mut svm, payer, _) = setup();
+        send_airdrop(&mut svm, &payer, 0, 1, 1_000);
+        let ticket = ticket_pda(&payer.pubkey(), 0);
+        let swap = |new_amount: u64, new_bump: u8| {
+            [new_amount.to_le_bytes().as_ref(), &[new_bump]].concat()
+        };
+
+        send_redeem(&mut svm, &payer, ticket, 1, 1_000, &swap(50, ticket.1)).unwrap();
+        let ticket_ref: Ticket = bytemuck::pod_read_unaligned(&account_data(&svm, &ticket.0));
+        assert_eq!(ticket_ref.amount.get(), 50);
+
+        // Stale expectation: the ticket no longer holds 1000
+        let before = account_data(&svm, &ticket.0);
+        let failed =
+            send_redeem(&mut svm, &payer, ticket, 1, 1_000, &swap(0, ticket.1)).unwrap_err();
+        assert_mapping_error(failed, MappingError::ValueMismatch);
+        assert_eq!(account_data(&svm, &ticket.0), before);
+
+        // The new value holds another bump
+        let failed = send_redeem(
+            &mut svm,
+            &payer,
+            ticket,
+            1,
+            50,
+            &swap(0, ticket.1.wrapping_sub(1)),
+        )
+        .unwrap_err();
+        assert_mapping_error(failed, MappingError::PdaMismatch);
+        assert_eq!(account_data(&svm, &ticket.0), before);
+    }
+
+    fn send_quote(
+        svm: &mut LiteSVM,
+        owner: &Keypair,
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let quote = Pubkey::find_program_address(
+            &[b"quotes".as_ref(), owner.pubkey().as_ref()],
+            &program_id(),
+        );
+        let quote_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(quote.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![10u8, mode, quote.1], args.to_vec()].concat(), // Discriminator for "Quote" instruction
+        };
+
+        let message = Message::new(&[quote_ix], Some(&owner.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[owner], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_quote_update_versioned() {
+        let (mut svm, payer, _) = setup();
+        let quote = Pubkey::find_program_address(
+            &[b"quotes".as_ref(), payer.pubkey().as_ref()],
+            &program_id(),
+        )
+        .0;
+        let version = |svm: &LiteSVM| {
+            let quote_account = svm
+                .get_account(&quote)
+                .expect("Could not retrieve account properly");
+            pda_pinocchio_mapping::entry_version(&quote_account.data)
+                .expect("Quote has no write counter")
+        };
+        let update = |amount: u64, expected_version: u64| {
+            [amount.to_le_bytes(), expected_version.to_le_bytes()].concat()
+        };
+
+        send_quote(&mut svm, &payer, 0, &1_000u64.to_le_bytes()).unwrap();
+        let read_version = version(&svm);
+
+        // A relayer updates the version it read
+        send_quote(&mut svm, &payer, 1, &update(900, read_version)).unwrap();
+        assert_eq!(version(&svm), read_version + 1);
+
+        // Another one, with the same read, is too late
+        let before = svm.get_account(&quote).unwrap().data;
+        let failed = send_quote(&mut svm, &payer, 1, &update(800, read_version)).unwrap_err();
+        assert_mapping_error(failed, MappingError::StaleVersion);
+        assert_eq!(svm.get_account(&quote).unwrap().data, before);
+
+        // On-chain counterpart of `entry_version`
+        let tx = send_quote(&mut svm, &payer, 2, &[]).unwrap();
+        let expected_log = format!("Quote version {}", read_version + 1);
+        assert!(tx.logs.iter().any(|log| log.contains(&expected_log)));
+    }
+
+    fn send_allowance(
+        svm: &mut LiteSVM,
+        owner: &Keypair,
+        spender: &Pubkey,
+        allowance: (Pubkey, u8),
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let allowance_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new_readonly(*spender, false),
+                AccountMeta::new(allowance.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![11u8, mode, allowance.1], args.to_vec()].concat(), // Discriminator for "Allowance" instruction
+        };
+
+        let message = Message::new(&[allowance_ix], Some(&owner.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[owner], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_allowance_double_mapping() {
+        let (mut svm, owner, spender) = setup();
+        let spender = spender.pubkey();
+
+        // Clients derive the entry from [name, key1, key2, bump]
+        let allowance = Pubkey::find_program_address(
+            &[
+                b"allowances".as_ref(),
+                owner.pubkey().as_ref(),
+                spender.as_ref(),
+            ],
+            &program_id(),
+        );
+
+        send_allowance(
+            &mut svm,
+            &owner,
+            &spender,
+            allowance,
+            0,
+            &500u64.to_le_bytes(),
+        )
+        .unwrap();
+
+        let allowance_account = svm
+            .get_account(&allowance.0)
+            .expect("Could not retrieve account properly");
+        assert_eq!(allowance_account.owner, program_id());
+        let header_len = HEADER_LEN;
+        assert_eq!(
+            allowance_account.data.len(),
+            header_len + core::mem::size_of::<Ticket>()
+        );
+        assert_eq!(allowance_account.data[..8], Ticket::DISCRIMINATOR);
+        assert_eq!(allowance_account.data[9], allowance.1);
+        let ticket_ref: Ticket =
+            bytemuck::pod_read_unaligned(&allowance_account.data[header_len..]);
+        assert_eq!(ticket_ref.amount.get(), 500);
+        assert_eq!(ticket_ref.bump, allowance.1);
+
+        let tx = send_allowance(&mut svm, &owner, &spender, allowance, 1, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Allowance 500")));
+
+        // The keys are ordered: [name, key2, key1] is another entry
+        let swapped = Pubkey::find_program_address(
+            &[
+                b"allowances".as_ref(),
+                spender.as_ref(),
+                owner.pubkey().as_ref(),
+            ],
+            &program_id(),
+        );
+        let failed = send_allowance(&mut svm, &owner, &spender, swapped, 1, &[]).unwrap_err();
+        assert_mapping_error(failed, MappingError::PdaMismatch);
+    }
+
+    /// Sends a Badge of `user`, with `fee_payer` paying the transaction fee
+    /// so that the balance of `user` only moves by the badge rent.
+    fn send_badge(
+        svm: &mut LiteSVM,
+        fee_payer: &Keypair,
+        user: &Keypair,
+        badge: (Pubkey, u8),
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let badge_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(badge.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![12u8, mode, badge.1], args.to_vec()].concat(), // Discriminator for "Badge" instruction
+        };
+
+        let message = Message::new(&[badge_ix], Some(&fee_payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[fee_payer, user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_badge_migrate() {
+        let (mut svm, payer, user) = setup();
+        let header_len = HEADER_LEN;
+        let badge = Pubkey::find_program_address(
+            &[b"badges".as_ref(), user.pubkey().as_ref()],
+            &program_id(),
+        );
+
+        send_badge(&mut svm, &payer, &user, badge, 0, &250u64.to_le_bytes()).unwrap();
+        let badge_account = svm
+            .get_account(&badge.0)
+            .expect("Could not retrieve account properly");
+        let v1_len = header_len + core::mem::size_of::<BadgeV1>();
+        assert_eq!(badge_account.data.len(), v1_len);
+        assert_eq!(badge_account.data[8], BadgeV1::VERSION);
+
+        // Growing to the new layout tops up the rent from the payer
+        let balance = lamports(&svm, &user.pubkey());
+        send_badge(&mut svm, &payer, &user, badge, 1, &[]).unwrap();
+
+        let badge_account = svm
+            .get_account(&badge.0)
+            .expect("Could not retrieve account properly");
+        let v2_len = header_len + core::mem::size_of::<Badge>();
+        assert_eq!(badge_account.data.len(), v2_len);
+        assert_eq!(badge_account.data[..8], Badge::DISCRIMINATOR);
+        assert_eq!(badge_account.data[8], Badge::VERSION);
+        assert_eq!(badge_account.data[9], badge.1);
+        let badge_ref: Badge = bytemuck::pod_read_unaligned(&badge_account.data[header_len..]);
+        assert_eq!(badge_ref.points.get(), 250);
+        assert_eq!(badge_ref.level.get(), 2);
+        assert_eq!(badge_ref.bump, badge.1);
+
+        let v1_rent = svm.minimum_balance_for_rent_exemption(v1_len);
+        let v2_rent = svm.minimum_balance_for_rent_exemption(v2_len);
+        assert_eq!(badge_account.lamports, v2_rent);
+        assert_eq!(
+            lamports(&svm, &user.pubkey()),
+            balance - (v2_rent - v1_rent)
+        );
+
+        // The header now describes `Badge`: the entry cannot be migrated twice
+        let failed = send_badge(&mut svm, &payer, &user, badge, 1, &[]).unwrap_err();
+        assert_mapping_error(failed, MappingError::VersionMismatch);
+        assert_eq!(svm.get_account(&badge.0).unwrap().data, badge_account.data);
+    }
+
+    /// Sends a Balance of `user`, with `fee_payer` paying the transaction fee
+    /// so that the balance of `user` only moves by the entry rent.
+    fn send_balance(
+        svm: &mut LiteSVM,
+        fee_payer: &Keypair,
+        user: &Keypair,
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let balance = Pubkey::find_program_address(
+            &[b"balances".as_ref(), user.pubkey().as_ref()],
+            &program_id(),
+        );
+        let balance_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(balance.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![13u8, mode], args.to_vec()].concat(), // Discriminator for "Balance" instruction, no bump
+        };
+
+        let message = Message::new(&[balance_ix], Some(&fee_payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[fee_payer, user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_balance_entry_without_bumpy() {
+        let (mut svm, payer, user) = setup();
+        let balance = Pubkey::find_program_address(
+            &[b"balances".as_ref(), user.pubkey().as_ref()],
+            &program_id(),
+        );
+        let stored = |svm: &LiteSVM| {
+            let balance_account = svm
+                .get_account(&balance.0)
+                .expect("Could not retrieve account properly");
+            assert_eq!(balance_account.data.len(), HEADER_LEN + 8);
+            // Bump-only header: no discriminator, the bump at byte 9
+            assert_eq!(balance_account.data[..9], [0; 9]);
+            assert_eq!(balance_account.data[9], balance.1);
+            u64::from_le_bytes(balance_account.data[HEADER_LEN..].try_into().unwrap())
+        };
+
+        // No bump passed: the program finds the canonical one
+        send_balance(&mut svm, &payer, &user, 0, &100u64.to_le_bytes()).unwrap();
+        assert_eq!(stored(&svm), 100);
+        let rent = lamports(&svm, &balance.0);
+        assert_eq!(rent, svm.minimum_balance_for_rent_exemption(HEADER_LEN + 8));
+
+        let failed = send_balance(&mut svm, &payer, &user, 0, &100u64.to_le_bytes()).unwrap_err();
+        assert_mapping_error(failed, MappingError::AlreadyInitialized);
+
+        send_balance(&mut svm, &payer, &user, 2, &250u64.to_le_bytes()).unwrap();
+        assert_eq!(stored(&svm), 250);
+
+        send_balance(&mut svm, &payer, &user, 3, &50u64.to_le_bytes()).unwrap();
+        assert_eq!(stored(&svm), 300);
+
+        let tx = send_balance(&mut svm, &payer, &user, 4, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Balance 300")));
+
+        // Removal refunds the rent to the user
+        let before = lamports(&svm, &user.pubkey());
+        send_balance(&mut svm, &payer, &user, 5, &[]).unwrap();
+        assert_eq!(lamports(&svm, &user.pubkey()), before + rent);
+        let closed = svm.get_account(&balance.0);
+        assert!(closed.is_none_or(|account| account.lamports == 0 && account.data.is_empty()));
+
+        let failed = send_balance(&mut svm, &payer, &user, 2, &1u64.to_le_bytes()).unwrap_err();
+        assert_mapping_error(failed, MappingError::Uninitialized);
+
+        // `set_entry` creates the entry again
+        send_balance(&mut svm, &payer, &user, 1, &7u64.to_le_bytes()).unwrap();
+        assert_eq!(stored(&svm), 7);
+        send_balance(&mut svm, &payer, &user, 1, &8u64.to_le_bytes()).unwrap();
+        assert_eq!(stored(&svm), 8);
+    }
+
+    fn send_position(
+        svm: &mut LiteSVM,
+        user: &Keypair,
+        position: (Pubkey, u8),
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let position_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(position.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![14u8, mode, position.1], args.to_vec()].concat(), // Discriminator for "Position" instruction
+        };
+
+        let message = Message::new(&[position_ix], Some(&user.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_position_find_matches_client() {
+        let (mut svm, user, _) = setup();
+        let position = Pubkey::find_program_address(
+            &[b"positions".as_ref(), user.pubkey().as_ref()],
+            &program_id(),
+        );
+
+        // On-chain `find` derives the same address and bump as the client
+        let tx = send_position(&mut svm, &user, position, 1, &[]).unwrap();
+        let expected_log = format!("Canonical bump {}", position.1);
+        assert!(tx.logs.iter().any(|log| log.contains(&expected_log)));
+    }
+
+    #[test]
+    pub fn test_position_strict_rejects_non_canonical_bump() {
+        let (mut svm, user, _) = setup();
+        let seeds = [b"positions".as_ref(), user.pubkey().as_ref()];
+        let canonical = Pubkey::find_program_address(&seeds, &program_id());
+
+        // Next bump below the canonical one that still derives a PDA
+        let non_canonical = (0..canonical.1)
+            .rev()
+            .find_map(|bump| {
+                Pubkey::create_program_address(&[seeds[0], seeds[1], &[bump]], &program_id())
+                    .ok()
+                    .map(|address| (address, bump))
+            })
+            .expect("No non-canonical bump");
+
+        let failed =
+            send_position(&mut svm, &user, non_canonical, 0, &100u64.to_le_bytes()).unwrap_err();
+        assert_mapping_error(failed, MappingError::NonCanonicalBump);
+        assert!(svm
+            .get_account(&non_canonical.0)
+            .is_none_or(|account| account.data.is_empty()));
+
+        send_position(&mut svm, &user, canonical, 0, &100u64.to_le_bytes()).unwrap();
+        let position_ref: Ticket = bytemuck::pod_read_unaligned(&account_data(&svm, &canonical.0));
+        assert_eq!(position_ref.amount.get(), 100);
+        assert_eq!(position_ref.bump, canonical.1);
+    }
+
+    /// Header-less ticket entry at `ticket`, owned by `owner_program`.
+    fn set_foreign_ticket(
+        svm: &mut LiteSVM,
+        ticket: &Pubkey,
+        owner_program: &Pubkey,
+        amount: u64,
+        bump: u8,
+    ) {
+        let data = bytemuck::bytes_of(&Ticket {
+            amount: amount.into(),
+            bump,
+        })
+        .to_vec();
+
+        svm.set_account(
+            *ticket,
+            solana_account::Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: *owner_program,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    fn send_lookup(
+        svm: &mut LiteSVM,
+        owner: &Keypair,
+        ticket: (Pubkey, u8),
+        program: &Pubkey,
+    ) -> litesvm::types::TransactionResult {
+        let lookup_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new_readonly(owner.pubkey(), true),
+                AccountMeta::new_readonly(ticket.0, false),
+            ],
+            data: [vec![15u8, ticket.1], program.to_bytes().to_vec()].concat(), // Discriminator for "Lookup" instruction
+        };
+
+        let message = Message::new(&[lookup_ix], Some(&owner.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[owner], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_lookup_foreign_mapping() {
+        let (mut svm, owner, _) = setup();
+        let other_program = Pubkey::new_unique();
+        let ticket = Pubkey::find_program_address(
+            &[Ticket::MAPPING_NAME, owner.pubkey().as_ref()],
+            &other_program,
+        );
+
+        set_foreign_ticket(&mut svm, &ticket.0, &other_program, 42, ticket.1);
+        let tx = send_lookup(&mut svm, &owner, ticket, &other_program).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Foreign ticket 42")));
+    }
+
+    #[test]
+    pub fn test_lookup_foreign_mapping_rejects_wrong_owner() {
+        let (mut svm, owner, _) = setup();
+        let other_program = Pubkey::new_unique();
+        let ticket = Pubkey::find_program_address(
+            &[Ticket::MAPPING_NAME, owner.pubkey().as_ref()],
+            &other_program,
+        );
+
+        // Right address, but the entry is not owned by the other program
+        set_foreign_ticket(&mut svm, &ticket.0, &Pubkey::new_unique(), 42, ticket.1);
+        let failed = send_lookup(&mut svm, &owner, ticket, &other_program).unwrap_err();
+        assert_mapping_error(failed, MappingError::WrongOwner);
+    }
+
+    #[test]
+    pub fn test_lookup_foreign_mapping_rejects_wrong_address() {
+        let (mut svm, owner, _) = setup();
+        let other_program = Pubkey::new_unique();
+
+        // Owned by the other program, but derived under this program's id
+        let ticket = Pubkey::find_program_address(
+            &[Ticket::MAPPING_NAME, owner.pubkey().as_ref()],
+            &program_id(),
+        );
+        set_foreign_ticket(&mut svm, &ticket.0, &other_program, 42, ticket.1);
+        let failed = send_lookup(&mut svm, &owner, ticket, &other_program).unwrap_err();
+        assert_mapping_error(failed, MappingError::PdaMismatch);
+    }
+
+    fn profile_pda(name: &[u8], user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[name, user.as_ref()], &program_id())
+    }
+
+    /// Sends a Profile of `user`, with `fee_payer` paying the transaction
+    /// fee so that the balance of `user` only moves by the entry rent.
+    fn send_profile(
+        svm: &mut LiteSVM,
+        fee_payer: &Keypair,
+        user: &Keypair,
+        entry: (Pubkey, u8),
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let profile_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(entry.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![16u8, mode, entry.1], args.to_vec()].concat(), // Discriminator for "Profile" instruction
+        };
+
+        let message = Message::new(&[profile_ix], Some(&fee_payer.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[fee_payer, user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    #[test]
+    pub fn test_profile_serialized_resizes_with_the_value() {
+        let (mut svm, payer, user) = setup();
+        let profile = profile_pda(b"profiles", &user.pubkey());
+        let referrer = [7u8; 32];
+        let referred = [&[1u8][..], &referrer[..], &[profile.1][..]].concat();
+
+        // Created at the serialized length: `None` and the bump
+        send_profile(&mut svm, &payer, &user, profile, 1, &[]).unwrap();
+        assert_eq!(account_data(&svm, &profile.0), vec![0, profile.1]);
+        assert_eq!(
+            lamports(&svm, &profile.0),
+            svm.minimum_balance_for_rent_exemption(2)
+        );
+
+        let failed = send_profile(&mut svm, &payer, &user, profile, 1, &[]).unwrap_err();
+        assert_mapping_error(failed, MappingError::AlreadyInitialized);
+
+        // A longer value grows the account, the user tops up the rent
+        let balance = lamports(&svm, &user.pubkey());
+        send_profile(&mut svm, &payer, &user, profile, 2, &referrer).unwrap();
+        assert_eq!(account_data(&svm, &profile.0), referred);
+        let rent_delta =
+            svm.minimum_balance_for_rent_exemption(34) - svm.minimum_balance_for_rent_exemption(2);
+        assert_eq!(lamports(&svm, &user.pubkey()), balance - rent_delta);
+
+        let tx = send_profile(&mut svm, &payer, &user, profile, 3, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Profile referred 1")));
+
+        // A shorter one shrinks it back and refunds the excess
+        let balance = lamports(&svm, &user.pubkey());
+        send_profile(&mut svm, &payer, &user, profile, 0, &[]).unwrap();
+        assert_eq!(account_data(&svm, &profile.0), vec![0, profile.1]);
+        assert_eq!(lamports(&svm, &user.pubkey()), balance + rent_delta);
+
+        let tx = send_profile(&mut svm, &payer, &user, profile, 3, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Profile referred 0")));
+    }
+
+    #[test]
+    pub fn test_profile_serialized_rejects_trailing_bytes() {
+        let (mut svm, payer, user) = setup();
+        let profile = profile_pda(b"profiles", &user.pubkey());
+
+        // A valid profile followed by a byte nothing deserializes
+        let data = vec![0, profile.1, 0];
+        svm.set_account(
+            profile.0,
+            solana_account::Account {
+                lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+                data,
+                owner: program_id(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+
+        let failed = send_profile(&mut svm, &payer, &user, profile, 3, &[]).unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    pub fn test_nickname_serialized_max_len() {
+        let (mut svm, payer, user) = setup();
+        let nickname = profile_pda(b"nicknames", &user.pubkey());
+        let max_len = 4 + Nickname::MAX_NICKNAME_LEN + 1;
+
+        // Always `MAX_LEN` bytes: the value followed by zeroes
+        send_profile(&mut svm, &payer, &user, nickname, 4, b"bob").unwrap();
+        let data = account_data(&svm, &nickname.0);
+        assert_eq!(data.len(), max_len);
+        assert_eq!(data[..8], [3, 0, 0, 0, b'b', b'o', b'b', nickname.1]);
+        assert!(data[8..].iter().all(|&byte| byte == 0));
+
+        let longest = [b'x'; Nickname::MAX_NICKNAME_LEN];
+        send_profile(&mut svm, &payer, &user, nickname, 4, &longest).unwrap();
+        assert_eq!(account_data(&svm, &nickname.0).len(), max_len);
+
+        let before = account_data(&svm, &nickname.0);
+        let too_long = [b'x'; Nickname::MAX_NICKNAME_LEN + 1];
+        let failed = send_profile(&mut svm, &payer, &user, nickname, 4, &too_long).unwrap_err();
+        assert_mapping_error(failed, MappingError::SizeMismatch);
+        assert_eq!(account_data(&svm, &nickname.0), before);
+    }
+
+    fn send_points(
+        svm: &mut LiteSVM,
+        user: &Keypair,
+        points: (Pubkey, u8),
+        mode: u8,
+        args: &[u8],
+    ) -> litesvm::types::TransactionResult {
+        let points_ix = Instruction {
+            program_id: program_id(),
+            accounts: vec![
+                AccountMeta::new(user.pubkey(), true),
+                AccountMeta::new(points.0, false),
+                AccountMeta::new_readonly(solana_sdk_ids::system_program::ID, false),
+            ],
+            data: [vec![17u8, mode, points.1], args.to_vec()].concat(), // Discriminator for "Points" instruction
+        };
+
+        let message = Message::new(&[points_ix], Some(&user.pubkey()));
+        let recent_blockhash = svm.latest_blockhash();
+        let transaction = Transaction::new(&[user], message, recent_blockhash);
+        svm.send_transaction(transaction)
+    }
+
+    fn points_pda(user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"points".as_ref(), user.as_ref()], &program_id())
+    }
+
+    fn no_entry(svm: &LiteSVM, address: &Pubkey) -> bool {
+        svm.get_account(address)
+            .is_none_or(|account| account.lamports == 0 && account.data.is_empty())
+    }
+
+    #[test]
+    pub fn test_points_get_or_default_creates_nothing() {
+        let (mut svm, user, _) = setup();
+        let points = points_pda(&user.pubkey());
+
+        let tx = send_points(&mut svm, &user, points, 0, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Points 0")));
+        assert!(no_entry(&svm, &points.0));
+    }
+
+    #[test]
+    pub fn test_points_get_or_create() {
+        let (mut svm, user, _) = setup();
+        let points = points_pda(&user.pubkey());
+
+        // Created from the default value, then mutated in place
+        send_points(&mut svm, &user, points, 1, &30u64.to_le_bytes()).unwrap();
+        let points_ref: Ticket = bytemuck::pod_read_unaligned(&account_data(&svm, &points.0));
+        assert_eq!(points_ref.amount.get(), 30);
+        assert_eq!(points_ref.bump, points.1);
+
+        // Existing entry: mutated only
+        send_points(&mut svm, &user, points, 1, &12u64.to_le_bytes()).unwrap();
+        let points_ref: Ticket = bytemuck::pod_read_unaligned(&account_data(&svm, &points.0));
+        assert_eq!(points_ref.amount.get(), 42);
+
+        let tx = send_points(&mut svm, &user, points, 0, &[]).unwrap();
+        assert!(tx.logs.iter().any(|log| log.contains("Points 42")));
+    }
+
+    #[test]
+    pub fn test_points_get_or_create_rejects_default_with_another_bump() {
+        let (mut svm, user, _) = setup();
+        let points = points_pda(&user.pubkey());
+
+        let failed = send_points(&mut svm, &user, points, 2, &30u64.to_le_bytes()).unwrap_err();
+        assert_eq!(
+            failed.err,
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+        );
+        assert!(no_entry(&svm, &points.0));
     }
 }
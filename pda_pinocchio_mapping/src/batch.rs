@@ -0,0 +1,119 @@
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{rent::Rent, Sysvar},
+};
+
+use crate::{Bumpy, Mapping, MappingKey};
+
+/**
+ * Failure of a batch operation: the position of the offending entry and the
+ * error it raised.
+ *
+ * Converts into the inner `ProgramError`, so `?` works in an instruction
+ * handler; match on it first to log or report `index`.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchError {
+    pub index: usize,
+    pub error: ProgramError,
+}
+
+impl From<BatchError> for ProgramError {
+    fn from(e: BatchError) -> Self {
+        e.error
+    }
+}
+
+/**
+ * Batch writes: one `(key, value)` per account, typically the instruction's
+ * remaining accounts.
+ *
+ * Every entry is written exactly as a single call would, in order. The first
+ * failing entry stops the batch and is reported with its index; returning
+ * the error fails the instruction, so the runtime discards the entries
+ * written before it.
+ *
+ * Usage:
+ * ```ignore
+ * let tickets = mapping!(b"tickets", payer => Ticket);
+ * if let Err(e) = tickets.set_many(&entries, remaining_accounts) {
+ *     log!("ticket {} failed", e.index);
+ *     return Err(e.into());
+ * }
+ * ```
+ */
+impl<'a, T: Pod + Bumpy> Mapping<'a, T> {
+    /**
+     * Creates or overwrites `entries[i]` in `accounts[i]`, see [`Mapping::set`].
+     *
+     * The Rent sysvar is read at most once for the whole batch; a mapping
+     * from [`MappingContext`](crate::MappingContext) does not read it at all.
+     *
+     * Returns:
+     * - `Ok(())` once every entry is written.
+     * - `BatchError` with `ProgramError::NotEnoughAccountKeys` if `entries`
+     *   and `accounts` differ in length, `index` being the first unpaired one.
+     * - `BatchError` with the error of [`Mapping::set`] for the first failing entry.
+     */
+    pub fn set_many<K: MappingKey>(
+        self,
+        entries: &[(K, T)],
+        accounts: &[AccountInfo],
+    ) -> Result<(), BatchError> {
+        check_pairing(entries.len(), accounts.len())?;
+
+        let rent;
+        let mapping = match self.rent {
+            Some(_) => self,
+            None => {
+                rent = Rent::get().map_err(|error| BatchError { index: 0, error })?;
+                self.with_rent(&rent)
+            }
+        };
+
+        for (index, ((key, value), account)) in entries.iter().zip(accounts).enumerate() {
+            mapping
+                .set(key, *value, account)
+                .map_err(|error| BatchError { index, error })?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Overwrites the existing `entries[i]` in `accounts[i]`, see [`Mapping::update`].
+     *
+     * Returns:
+     * - `Ok(())` once every entry is written.
+     * - `BatchError` with `ProgramError::NotEnoughAccountKeys` if `entries`
+     *   and `accounts` differ in length, `index` being the first unpaired one.
+     * - `BatchError` with the error of [`Mapping::update`] for the first failing entry.
+     */
+    pub fn update_many<K: MappingKey>(
+        self,
+        entries: &[(K, T)],
+        accounts: &[AccountInfo],
+    ) -> Result<(), BatchError> {
+        check_pairing(entries.len(), accounts.len())?;
+
+        for (index, ((key, value), account)) in entries.iter().zip(accounts).enumerate() {
+            self.update(key, *value, account)
+                .map_err(|error| BatchError { index, error })?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Fails unless there is exactly one account per entry.
+ */
+fn check_pairing(entries: usize, accounts: usize) -> Result<(), BatchError> {
+    if entries != accounts {
+        return Err(BatchError {
+            index: entries.min(accounts),
+            error: ProgramError::NotEnoughAccountKeys,
+        });
+    }
+    Ok(())
+}
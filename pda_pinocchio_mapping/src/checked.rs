@@ -0,0 +1,127 @@
+use bytemuck::{CheckedBitPattern, NoUninit};
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{layout, Bumpy, Mapping, MappingError, MappingKey};
+
+/**
+ * Values with restricted bit patterns: `bool` flags, fieldless enums, and
+ * structs made of them, which are not `Pod`.
+ *
+ * Writes only need the value to have no padding (`NoUninit`). Every read
+ * checks the stored bytes first (`CheckedBitPattern`) and fails with
+ * `ProgramError::InvalidAccountData` on a byte that is no valid `bool` or
+ * enum variant, instead of producing an invalid value.
+ *
+ * Usage:
+ * ```ignore
+ * #[repr(u8)]
+ * #[derive(Clone, Copy, CheckedBitPattern, NoUninit)]
+ * pub enum Status { Open, Settled, Cancelled }
+ *
+ * #[repr(C)]
+ * #[derive(Clone, Copy, CheckedBitPattern, NoUninit, Bumpy)]
+ * pub struct Order { pub status: Status, pub active: bool, pub bump: u8 }
+ *
+ * let orders = mapping!(b"orders", payer => Order);
+ * orders.set_checked(maker.key(), order, order_account)?;
+ * let order = orders.get_checked(maker.key(), bump, order_account)?;
+ * ```
+ */
+impl<'a, T: CheckedBitPattern + NoUninit + Bumpy> Mapping<'a, T> {
+    /**
+     * Creates or overwrites the entry associated with `(name, key)`, see
+     * [`Mapping::set`].
+     */
+    pub fn set_checked<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        self.check_address(key, value.bump(), account)?;
+
+        if account.owner() != self.program_id {
+            if account.owner() != &pinocchio_system::ID {
+                return Err(MappingError::WrongOwner.into());
+            }
+            self.create_account(key, value.bump(), account, self.data_len())?;
+            self.init_header(account, value.bump())?;
+        }
+        self.write_value(account, value.bump(), value)
+    }
+
+    /**
+     * Overwrites the value of an existing entry, see [`Mapping::update`].
+     */
+    pub fn update_checked<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        self.check_address(key, value.bump(), account)?;
+        self.check_owner(account)?;
+
+        self.write_value(account, value.bump(), value)
+    }
+
+    /**
+     * Borrows the value of an existing entry in place, see [`Mapping::get`].
+     * The account data stays borrowed until the returned guard is dropped.
+     *
+     * Returns the errors of [`Mapping::get`], and
+     * `ProgramError::InvalidAccountData` if the stored bytes are not a valid `T`.
+     */
+    pub fn get_checked<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        let t_ref: &T = layout::checked_ref(&data[header_len..])?;
+        if t_ref.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::checked::from_bytes::<T>(&data[header_len..])
+        }))
+    }
+
+    /**
+     * Copies the value of an existing entry, at any alignment, see
+     * [`Mapping::read`].
+     *
+     * Returns the errors of [`Mapping::read`], and
+     * `ProgramError::InvalidAccountData` if the stored bytes are not a valid `T`.
+     */
+    pub fn read_checked<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> Result<T, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        let value: T = layout::checked_read(&data[self.header_len()..])?;
+        if value.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,105 @@
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{rent::Rent, Sysvar},
+};
+
+use crate::{Bumpy, DoubleMapping, Mapping, VaultMapping};
+
+/**
+ * Per-instruction state shared by every mapping an instruction touches.
+ *
+ * Built once at the start of an instruction, it holds the program id, the
+ * payer and the Rent sysvar, fetched a single time. The handles it returns
+ * use the cached rent, so creating or resizing many entries costs one
+ * `sol_get_rent_sysvar` syscall instead of one per entry. Handles borrow the
+ * context and are `Copy`, so they can be built once and reused in loops.
+ *
+ * Usage:
+ * ```ignore
+ * let ctx = MappingContext::new(&crate::ID, payer)?.with_system_program(system_program)?;
+ * let balances = ctx.mapping::<Balance>(b"balances");
+ * for (user, account) in users.iter().zip(balance_accounts) {
+ *     balances.set(user, Balance::new(amount, bump), account)?;
+ * }
+ * ```
+ */
+pub struct MappingContext<'a> {
+    pub program_id: &'a Pubkey,
+    pub payer: &'a AccountInfo,
+    pub rent: Rent,
+    pub system_program: Option<&'a AccountInfo>,
+}
+
+impl<'a> MappingContext<'a> {
+    /**
+     * Builds a context for `program_id`, reading the Rent sysvar once.
+     *
+     * Returns:
+     * - `Ok(MappingContext)` with the cached rent.
+     * - `ProgramError` if the Rent sysvar cannot be read.
+     */
+    pub fn new(program_id: &'a Pubkey, payer: &'a AccountInfo) -> Result<Self, ProgramError> {
+        Ok(Self::with_rent(program_id, payer, Rent::get()?))
+    }
+
+    /**
+     * Builds a context around an already known `rent`, e.g. deserialized
+     * from the Rent sysvar account passed to the instruction.
+     */
+    pub fn with_rent(program_id: &'a Pubkey, payer: &'a AccountInfo, rent: Rent) -> Self {
+        Self {
+            program_id,
+            payer,
+            rent,
+            system_program: None,
+        }
+    }
+
+    /**
+     * Records the system program account passed to the instruction.
+     *
+     * Pinocchio CPIs do not need the account itself, so this only checks
+     * once that the caller passed the real system program.
+     *
+     * Returns:
+     * - `Ok(MappingContext)` holding the account.
+     * - `ProgramError::IncorrectProgramId` if `system_program` is another account.
+     */
+    pub fn with_system_program(
+        mut self,
+        system_program: &'a AccountInfo,
+    ) -> Result<Self, ProgramError> {
+        if system_program.key() != &pinocchio_system::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        self.system_program = Some(system_program);
+        Ok(self)
+    }
+
+    /**
+     * Returns a handle on the mapping `name` of this program, using the
+     * cached rent. See [`Mapping::new`].
+     */
+    pub fn mapping<T: ?Sized>(&self, name: &'static [u8]) -> Mapping<'_, T> {
+        Mapping::new(self.program_id, name, self.payer).with_rent(&self.rent)
+    }
+
+    /**
+     * Returns a handle on the two-level mapping `name` of this program,
+     * using the cached rent. See [`DoubleMapping::new`].
+     */
+    pub fn double_mapping<T: Pod + Bumpy>(&self, name: &'static [u8]) -> DoubleMapping<'_, T> {
+        DoubleMapping::new(self.program_id, name, self.payer).with_rent(&self.rent)
+    }
+
+    /**
+     * Returns a handle on the SOL vaults `name` of this program, using the
+     * cached rent. See [`VaultMapping::new`].
+     */
+    pub fn vault_mapping(&self, name: &'static [u8]) -> VaultMapping<'_> {
+        VaultMapping::new(self.program_id, name, self.payer).with_rent(&self.rent)
+    }
+}
@@ -0,0 +1,208 @@
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::rent::Rent,
+    ProgramResult,
+};
+
+use crate::{Bumpy, Mapping, MappingKey, MappingValue};
+
+/**
+ * Two-level mapping, the PDA counterpart of Solidity's
+ * `mapping(a => mapping(b => T))`.
+ *
+ * Entries live at the PDA derived from `[name, key1, key2, bump]`. Every
+ * method forwards to [`Mapping`] with the `(key1, key2)` pair as key, so
+ * derivation, creation and validation are exactly those of a single mapping.
+ */
+pub struct DoubleMapping<'a, T> {
+    mapping: Mapping<'a, T>,
+}
+
+impl<T> Clone for DoubleMapping<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for DoubleMapping<'_, T> {}
+
+impl<'a, T: Pod + Bumpy> DoubleMapping<'a, T> {
+    pub fn new(program_id: &'a Pubkey, name: &'static [u8], payer: &'a AccountInfo) -> Self {
+        Self {
+            mapping: Mapping::new(program_id, name, payer),
+        }
+    }
+
+    /**
+     * Stores an [`AccountHeader`](crate::AccountHeader) in front of every
+     * value, see [`Mapping::with_header`].
+     */
+    pub fn with_header(mut self) -> Self
+    where
+        T: MappingValue,
+    {
+        self.mapping = self.mapping.with_header();
+        self
+    }
+
+    /**
+     * Only accepts canonical bumps when creating entries, see [`Mapping::strict`].
+     */
+    pub fn strict(mut self) -> Self {
+        self.mapping = self.mapping.strict();
+        self
+    }
+
+    /**
+     * Uses `rent` instead of the Rent sysvar, see [`Mapping::with_rent`].
+     */
+    pub fn with_rent(mut self, rent: &'a Rent) -> Self {
+        self.mapping = self.mapping.with_rent(rent);
+        self
+    }
+
+    /**
+     * Returns the canonical PDA and bump of `(key1, key2)`, see [`Mapping::find`].
+     */
+    pub fn find<K1, K2>(&self, key1: &K1, key2: &K2) -> Result<(Pubkey, u8), ProgramError>
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.find(&(key1, key2))
+    }
+
+    /**
+     * Creates or overwrites the entry at `(key1, key2)`, see [`Mapping::set`].
+     */
+    pub fn set<K1, K2>(self, key1: &K1, key2: &K2, value: T, account: &AccountInfo) -> ProgramResult
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.set(&(key1, key2), value, account)
+    }
+
+    /**
+     * Overwrites the existing entry at `(key1, key2)`, see [`Mapping::update`].
+     */
+    pub fn update<K1, K2>(
+        self,
+        key1: &K1,
+        key2: &K2,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.update(&(key1, key2), value, account)
+    }
+
+    /**
+     * Creates the entry at `(key1, key2)` for the first time, see [`Mapping::create`].
+     */
+    pub fn create<K1, K2>(
+        self,
+        key1: &K1,
+        key2: &K2,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.create(&(key1, key2), value, account)
+    }
+
+    /**
+     * Borrows the entry at `(key1, key2)`, see [`Mapping::get`]. The
+     * account data stays borrowed until the returned guard is dropped.
+     */
+    pub fn get<'b, K1, K2>(
+        self,
+        key1: &K1,
+        key2: &K2,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError>
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.get(&(key1, key2), bump, account)
+    }
+
+    /**
+     * Mutably borrows the entry at `(key1, key2)`, see [`Mapping::get_mut`].
+     */
+    pub fn get_mut<'b, K1, K2>(
+        self,
+        key1: &K1,
+        key2: &K2,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<RefMut<'b, T>, ProgramError>
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.get_mut(&(key1, key2), bump, account)
+    }
+
+    /**
+     * Closes the entry at `(key1, key2)` and refunds its rent to `recipient`,
+     * see [`Mapping::remove`].
+     */
+    pub fn remove<K1, K2>(
+        self,
+        key1: &K1,
+        key2: &K2,
+        bump: u8,
+        account: &AccountInfo,
+        recipient: &AccountInfo,
+    ) -> ProgramResult
+    where
+        K1: MappingKey + ?Sized,
+        K2: MappingKey + ?Sized,
+    {
+        self.mapping.remove(&(key1, key2), bump, account, recipient)
+    }
+}
+
+/**
+ * Constructs a [`DoubleMapping`] using the caller crate’s program ID.
+ *
+ * Same resolution of `crate::ID` as [`mapping!`](crate::mapping).
+ *
+ * Usage:
+ * ```ignore
+ * let allowances = double_mapping!(b"allowances", payer_account => Allowance);
+ * allowances.set(owner.key(), spender.key(), allowance, allowance_account)?;
+ * ```
+ *
+ * Requirements:
+ * - The caller crate must expose a public `ID: Pubkey` constant.
+ * - `name` must be a byte-slice identifier.
+ * - `payer` must be an `AccountInfo` reference.
+ */
+#[macro_export]
+macro_rules! double_mapping {
+    ($name:expr, $payer:expr => $value:ty) => {{
+        // Fail early if ID doesn't exist or is the wrong type
+        let program_id: &pinocchio::pubkey::Pubkey = &crate::ID;
+
+        $crate::DoubleMapping::<$value>::new(program_id, $name, $payer)
+    }};
+    ($name:expr, $payer:expr) => {{
+        // Fail early if ID doesn't exist or is the wrong type
+        let program_id: &pinocchio::pubkey::Pubkey = &crate::ID;
+
+        $crate::DoubleMapping::new(program_id, $name, $payer)
+    }};
+}
@@ -0,0 +1,220 @@
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::{AccountInfo, Ref, RefMut},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{layout, AccountHeader, Mapping, MappingError, MappingKey};
+
+/**
+ * Header-managed bumps: values only need to be `Pod`.
+ *
+ * With [`Mapping::with_bump_header`] (or [`Mapping::with_header`]) every
+ * entry starts with an [`AccountHeader`] that records the PDA bump. The
+ * `*_entry` methods below take the bump from that header instead of from
+ * the value, so value types carry no PDA plumbing and need no `Bumpy`.
+ * The bump is only supplied on creation, or computed on-chain.
+ *
+ * Usage:
+ * ```ignore
+ * let balances = mapping!(b"balances", payer => Balance).with_bump_header();
+ * balances.create_entry(user.key(), None, Balance::zeroed(), balance_account)?;
+ * let balance = balances.get_entry(user.key(), balance_account)?;
+ * ```
+ *
+ * All `*_entry` methods fail with `MappingError::MissingHeader` on a
+ * mapping without header.
+ */
+impl<'a, T: Pod> Mapping<'a, T> {
+    /**
+     * Stores an [`AccountHeader`] holding the entry bump in front of every
+     * value of this mapping.
+     *
+     * Unlike [`Mapping::with_header`], `T` needs no [`MappingValue`](crate::MappingValue):
+     * the discriminator and version are left zero. Has no effect on a mapping
+     * that already has a header.
+     */
+    pub fn with_bump_header(mut self) -> Self {
+        if self.header.is_none() {
+            self.header = Some(AccountHeader::default());
+        }
+        self
+    }
+
+    /**
+     * Creates and initializes the PDA account associated with `(name, key)`.
+     *
+     * This method derives the PDA using:
+     *   - the mapping's static `name`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
+     *   - `bump`, or the canonical bump found with `find_program_address`
+     *     when `bump` is `None`.
+     *
+     * Behavior:
+     * - Creates the account like [`Mapping::create`] and records the bump
+     *   in the entry header.
+     * - Fails if the entry already exists.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` if the entry is created and initialized.
+     * - `MappingError::MissingHeader` if the mapping has no header.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::AlreadyInitialized` if the PDA already exists and is owned.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     */
+    pub fn create_entry<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: Option<u8>,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        self.require_header()?;
+        let bump = match bump {
+            Some(bump) => bump,
+            None => self.find(key)?.1,
+        };
+        self.check_address(key, bump, account)?;
+
+        if account.owner() == self.program_id {
+            return Err(MappingError::AlreadyInitialized.into());
+        }
+        if account.owner() != &pinocchio_system::ID {
+            return Err(MappingError::WrongOwner.into());
+        }
+
+        self.create_account(key, bump, account, self.data_len())?;
+        self.init_header(account, bump)?;
+        self.write_value(account, bump, value)
+    }
+
+    /**
+     * Creates or overwrites the entry associated with `(name, key)`.
+     *
+     * - If the entry exists, `bump` is ignored and the stored one is used,
+     *   as in [`Mapping::update_entry`].
+     * - Otherwise the entry is created as in [`Mapping::create_entry`].
+     */
+    pub fn set_entry<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: Option<u8>,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        if account.owner() == self.program_id {
+            self.update_entry(key, value, account)
+        } else {
+            self.create_entry(key, bump, value, account)
+        }
+    }
+
+    /**
+     * Overwrites the value of an existing entry, using the bump stored in
+     * its header to verify the PDA.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` if the value is successfully written.
+     * - `MappingError::MissingHeader` if the mapping has no header.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     */
+    pub fn update_entry<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        self.check_owner(account)?;
+        let bump = self.stored_bump(account)?;
+        self.check_address(key, bump, account)?;
+
+        self.write_value(account, bump, value)
+    }
+
+    /**
+     * Borrows the value of an existing entry, using the bump stored in its
+     * header to verify the PDA.
+     *
+     * Like [`Mapping::get`], returns a `Ref` guard into the account data,
+     * which stays borrowed until the guard is dropped.
+     */
+    pub fn get_entry<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError> {
+        self.check_owner(account)?;
+        let bump = self.stored_bump(account)?;
+        self.check_address(key, bump, account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        layout::value_ref::<T>(&data[header_len..])?;
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes::<T>(&data[header_len..])
+        }))
+    }
+
+    /**
+     * Copies the value of an existing entry, using the bump stored in its
+     * header. Works at any alignment, see [`Mapping::read`].
+     */
+    pub fn read_entry<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        account: &AccountInfo,
+    ) -> Result<T, ProgramError> {
+        self.check_owner(account)?;
+        let bump = self.stored_bump(account)?;
+        self.check_address(key, bump, account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        Ok(layout::read_value(&data[self.header_len()..])?)
+    }
+
+    /**
+     * Mutably borrows the value of an existing entry, see
+     * [`Mapping::get_entry`] and [`Mapping::get_mut`].
+     */
+    pub fn get_entry_mut<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        account: &'b AccountInfo,
+    ) -> Result<RefMut<'b, T>, ProgramError> {
+        self.check_owner(account)?;
+        let bump = self.stored_bump(account)?;
+        self.check_address(key, bump, account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        layout::value_mut::<T>(&mut data[header_len..])?;
+        Ok(RefMut::map(data, |data| {
+            bytemuck::from_bytes_mut::<T>(&mut data[header_len..])
+        }))
+    }
+
+    /**
+     * Closes an existing entry and refunds its rent to `recipient`, using
+     * the bump stored in its header. See [`Mapping::remove`].
+     */
+    pub fn remove_entry<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        account: &AccountInfo,
+        recipient: &AccountInfo,
+    ) -> ProgramResult {
+        self.check_owner(account)?;
+        let bump = self.stored_bump(account)?;
+        self.remove(key, bump, account, recipient)
+    }
+}
@@ -0,0 +1,127 @@
+use pinocchio::program_error::ProgramError;
+
+/**
+ * Errors returned by mapping operations.
+ *
+ * Each variant converts into `ProgramError::Custom` with a fixed code. Codes
+ * start at 7000 to stay clear of small program-local codes and of Anchor's
+ * 6000 range, and are never renumbered.
+ *
+ * | code | variant                 |
+ * |------|-------------------------|
+ * | 7000 | `PdaMismatch`           |
+ * | 7001 | `WrongOwner`            |
+ * | 7002 | `SizeMismatch`          |
+ * | 7003 | `Misaligned`            |
+ * | 7004 | `AlreadyInitialized`    |
+ * | 7005 | `Uninitialized`         |
+ * | 7006 | `DiscriminatorMismatch` |
+ * | 7007 | `VersionMismatch`       |
+ * | 7008 | `MissingHeader`         |
+ * | 7009 | `NonCanonicalBump`      |
+ * | 7010 | `ValueMismatch`         |
+ * | 7011 | `StaleVersion`          |
+ * | 7012 | `FlagsMismatch`         |
+ */
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappingError {
+    /// The passed account is not the PDA derived from `[name, key, bump]`.
+    PdaMismatch = 7000,
+    /// The account is owned by neither the mapping program nor the system program.
+    WrongOwner = 7001,
+    /// The account data length does not match the value type.
+    SizeMismatch = 7002,
+    /// The account data is not aligned for the value type.
+    Misaligned = 7003,
+    /// `create` was called on an entry that already exists.
+    AlreadyInitialized = 7004,
+    /// The entry does not exist yet.
+    Uninitialized = 7005,
+    /// The entry header holds the discriminator of another type.
+    DiscriminatorMismatch = 7006,
+    /// The entry header holds another schema version of the type.
+    VersionMismatch = 7007,
+    /// The operation needs a mapping whose entries carry an `AccountHeader`.
+    MissingHeader = 7008,
+    /// Strict mode: the bump is not the canonical bump of the entry.
+    NonCanonicalBump = 7009,
+    /// `update_if`: the stored value is not the expected one.
+    ValueMismatch = 7010,
+    /// `update_versioned`: the entry was written since the expected version.
+    StaleVersion = 7011,
+    /// The entry header holds other layout flags, e.g. no write counter.
+    FlagsMismatch = 7012,
+}
+
+impl From<MappingError> for ProgramError {
+    fn from(e: MappingError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl TryFrom<u32> for MappingError {
+    type Error = ProgramError;
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        match code {
+            7000 => Ok(MappingError::PdaMismatch),
+            7001 => Ok(MappingError::WrongOwner),
+            7002 => Ok(MappingError::SizeMismatch),
+            7003 => Ok(MappingError::Misaligned),
+            7004 => Ok(MappingError::AlreadyInitialized),
+            7005 => Ok(MappingError::Uninitialized),
+            7006 => Ok(MappingError::DiscriminatorMismatch),
+            7007 => Ok(MappingError::VersionMismatch),
+            7008 => Ok(MappingError::MissingHeader),
+            7009 => Ok(MappingError::NonCanonicalBump),
+            7010 => Ok(MappingError::ValueMismatch),
+            7011 => Ok(MappingError::StaleVersion),
+            7012 => Ok(MappingError::FlagsMismatch),
+            _ => Err(ProgramError::InvalidArgument),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl MappingError {
+    /**
+     * Decodes a `ProgramError::Custom` code into a readable message.
+     *
+     * Meant for clients and tests inspecting a failed transaction; codes that
+     * do not belong to this crate are reported as unknown.
+     */
+    pub fn describe(code: u32) -> std::string::String {
+        use std::string::ToString;
+
+        match MappingError::try_from(code) {
+            Ok(e) => e.to_string(),
+            Err(_) => std::format!("Unknown custom program error: {code}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for MappingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            MappingError::PdaMismatch => "Mapping: account does not match the derived PDA",
+            MappingError::WrongOwner => "Mapping: account is owned by another program",
+            MappingError::SizeMismatch => "Mapping: account data size does not match the value",
+            MappingError::Misaligned => "Mapping: account data is misaligned for the value",
+            MappingError::AlreadyInitialized => "Mapping: entry already initialized",
+            MappingError::Uninitialized => "Mapping: entry is not initialized",
+            MappingError::DiscriminatorMismatch => "Mapping: entry holds another value type",
+            MappingError::VersionMismatch => "Mapping: entry holds another schema version",
+            MappingError::MissingHeader => "Mapping: operation needs a mapping with header",
+            MappingError::NonCanonicalBump => "Mapping: bump is not the canonical bump",
+            MappingError::ValueMismatch => "Mapping: stored value differs from the expected one",
+            MappingError::StaleVersion => "Mapping: entry was written since the expected version",
+            MappingError::FlagsMismatch => "Mapping: entry header holds other layout flags",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MappingError {}
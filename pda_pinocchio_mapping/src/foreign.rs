@@ -0,0 +1,186 @@
+use bytemuck::Pod;
+use core::marker::PhantomData;
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    check_owner, header,
+    key::{check_address, find_address},
+    layout, AccountHeader, Bumpy, MappingError, MappingKey, MappingValue,
+};
+
+/**
+ * Read-only view of a mapping owned by another program.
+ *
+ * Entries are derived from `[name, key seeds.., bump]` under the other
+ * program's id and must be owned by that program, exactly as that program's
+ * own [`Mapping`](crate::Mapping) would create them. There is no payer and no
+ * write method: only the owning program can change its entries.
+ *
+ * Usage:
+ * ```ignore
+ * let positions = ForeignMapping::<Position>::new(&LENDING_PROGRAM_ID, b"positions");
+ * let position = positions.get(user.key(), bump, position_account)?;
+ * ```
+ *
+ * The header layout must match the one of the owning program: use
+ * [`ForeignMapping::with_header`] or [`ForeignMapping::with_bump_header`]
+ * if it stores entries with an [`AccountHeader`].
+ */
+pub struct ForeignMapping<'a, T> {
+    pub program_id: &'a Pubkey,
+    pub name: &'static [u8],
+    header: Option<AccountHeader>,
+    _value: PhantomData<T>,
+}
+
+impl<T> Clone for ForeignMapping<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ForeignMapping<'_, T> {}
+
+impl<'a, T: Pod> ForeignMapping<'a, T> {
+    pub fn new(program_id: &'a Pubkey, name: &'static [u8]) -> Self {
+        Self {
+            program_id,
+            name,
+            header: None,
+            _value: PhantomData,
+        }
+    }
+
+    /**
+     * Expects an [`AccountHeader`] describing `T` in front of every value,
+     * see [`Mapping::with_header`](crate::Mapping::with_header).
+     */
+    pub fn with_header(mut self) -> Self
+    where
+        T: MappingValue,
+    {
+        let flags = self.header.map_or(0, |header| header.flags);
+        self.header = Some(AccountHeader::new::<T>(0).with_flags(flags));
+        self
+    }
+
+    /**
+     * Expects an [`AccountHeader`] holding only the entry bump, see
+     * [`Mapping::with_bump_header`](crate::Mapping::with_bump_header).
+     */
+    pub fn with_bump_header(mut self) -> Self {
+        if self.header.is_none() {
+            self.header = Some(AccountHeader::default());
+        }
+        self
+    }
+
+    /**
+     * Expects a write counter after the header, see
+     * [`Mapping::with_write_counter`](crate::Mapping::with_write_counter).
+     */
+    pub fn with_write_counter(mut self) -> Self {
+        let header = self.header.unwrap_or_default();
+        self.header = Some(header.with_flags(header.flags | AccountHeader::WRITE_COUNTER));
+        self
+    }
+
+    /**
+     * Returns the canonical PDA and bump of the entry associated with
+     * `(name, key)` in the other program, see [`Mapping::find`](crate::Mapping::find).
+     */
+    pub fn find<K: MappingKey + ?Sized>(&self, key: &K) -> Result<(Pubkey, u8), ProgramError> {
+        find_address(self.program_id, self.name, key)
+    }
+
+    /**
+     * Reads the value stored by the other program for `(name, key)`.
+     *
+     * Behavior:
+     * - Verifies that `account` is the PDA derived from `[name, key, bump]`
+     *   under `program_id`.
+     * - Verifies that `account` is owned by `program_id`.
+     * - Returns a `Ref` guard into the account data, no copy is made. The
+     *   account data stays borrowed until the guard is dropped.
+     *
+     * Safety & validation:
+     * - Ensures the data length matches the header (if any) plus `T`.
+     * - Ensures the header, if any, matches `T` and `bump`.
+     * - Ensures proper memory alignment for bytemuck casting.
+     * - Ensures the bump stored in the value matches `bump`.
+     *
+     * Returns:
+     * - `Ok(Ref<T>)` pointing at the stored value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by any other program.
+     * - `MappingError::SizeMismatch` or `MappingError::Misaligned` if the stored
+     *   data layout does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     * - `ProgramError::AccountBorrowFailed` if the account data is already
+     *   mutably borrowed.
+     */
+    pub fn get<'b, K: MappingKey + ?Sized>(
+        &self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError>
+    where
+        T: Bumpy,
+    {
+        let value = self.read(key, bump, account)?;
+        if value.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(value)
+    }
+
+    /**
+     * Borrows the value of an entry whose bump is recorded in its header,
+     * see [`Mapping::get_entry`](crate::Mapping::get_entry).
+     *
+     * Returns the errors of [`ForeignMapping::get`], and
+     * `MappingError::MissingHeader` on a mapping without header.
+     */
+    pub fn get_entry<'b, K: MappingKey + ?Sized>(
+        &self,
+        key: &K,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError> {
+        if self.header.is_none() {
+            return Err(MappingError::MissingHeader.into());
+        }
+        check_owner(self.program_id, account)?;
+
+        let bump = header::stored_bump(&account.try_borrow_data()?)?;
+        self.read(key, bump, account)
+    }
+
+    /**
+     * Checks address, owner and layout of an entry, and borrows its value.
+     */
+    fn read<'b, K: MappingKey + ?Sized>(
+        &self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError> {
+        check_address(self.program_id, self.name, key, bump, account.key())?;
+        check_owner(self.program_id, account)?;
+
+        let data = account.try_borrow_data()?;
+        header::check_entry_data(self.header, &data, core::mem::size_of::<T>(), bump)?;
+
+        let header_len = header::header_len(self.header);
+        layout::value_ref::<T>(&data[header_len..])?;
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes::<T>(&data[header_len..])
+        }))
+    }
+}
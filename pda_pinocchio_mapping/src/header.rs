@@ -0,0 +1,335 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::MappingError;
+
+/**
+ * Size in bytes of the [`AccountHeader`] placed in front of header-enabled
+ * mapping values. Kept at 16 so that the value behind it stays 8-byte aligned.
+ */
+pub const HEADER_LEN: usize = core::mem::size_of::<AccountHeader>();
+
+/**
+ * Size in bytes of the write counter stored right after the header of
+ * mappings built with [`Mapping::with_write_counter`](crate::Mapping::with_write_counter).
+ */
+pub const WRITE_COUNTER_LEN: usize = core::mem::size_of::<u64>();
+
+/**
+ * Identifies the type stored in header-enabled mapping entries.
+ *
+ * Usage:
+ * ```ignore
+ * impl MappingValue for Share {
+ *     const DISCRIMINATOR: [u8; 8] = discriminator("Share");
+ *     const VERSION: u8 = 1;
+ * }
+ * ```
+ */
+pub trait MappingValue {
+    /// Written at offset 0 of every entry, lets clients filter accounts by type.
+    const DISCRIMINATOR: [u8; 8];
+    /// Layout version of the value, bump it whenever the struct changes.
+    const VERSION: u8 = 0;
+}
+
+/**
+ * Derives an 8-byte discriminator from a type name (64-bit FNV-1a).
+ *
+ * Being a `const fn`, the result can initialize
+ * [`MappingValue::DISCRIMINATOR`] at compile time.
+ */
+pub const fn discriminator(type_name: &str) -> [u8; 8] {
+    let bytes = type_name.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash.to_le_bytes()
+}
+
+/**
+ * Header stored in front of the value when a mapping is built with
+ * [`Mapping::with_header`](crate::Mapping::with_header).
+ *
+ * Layout (16 bytes):
+ * - `[0..8]`   discriminator of the value type,
+ * - `[8]`      schema version of the value type,
+ * - `[9]`      bump of the entry PDA,
+ * - `[10]`     flags, see [`AccountHeader::WRITE_COUNTER`],
+ * - `[11..16]` reserved, zero.
+ */
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct AccountHeader {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    pub bump: u8,
+    pub flags: u8,
+    _reserved: [u8; 5],
+}
+
+impl AccountHeader {
+    /// Flag: a `u64` write counter (LE) follows the header, see [`entry_version`].
+    pub const WRITE_COUNTER: u8 = 1 << 0;
+
+    pub fn new<T: MappingValue>(bump: u8) -> Self {
+        Self {
+            discriminator: T::DISCRIMINATOR,
+            version: T::VERSION,
+            bump,
+            flags: 0,
+            _reserved: [0; 5],
+        }
+    }
+
+    pub(crate) fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn has_write_counter(&self) -> bool {
+        self.flags & Self::WRITE_COUNTER != 0
+    }
+
+    pub(crate) fn with_bump(mut self, bump: u8) -> Self {
+        self.bump = bump;
+        self
+    }
+
+    /**
+     * Checks that a stored header describes the same layout, type, version
+     * and bump as `self`.
+     *
+     * Fails with `FlagsMismatch`, `DiscriminatorMismatch`, `VersionMismatch`
+     * or `PdaMismatch`, in that order.
+     */
+    pub(crate) fn check(&self, stored: &AccountHeader) -> Result<(), MappingError> {
+        if stored.flags != self.flags {
+            return Err(MappingError::FlagsMismatch);
+        }
+        if stored.discriminator != self.discriminator {
+            return Err(MappingError::DiscriminatorMismatch);
+        }
+        if stored.version != self.version {
+            return Err(MappingError::VersionMismatch);
+        }
+        if stored.bump != self.bump {
+            return Err(MappingError::PdaMismatch);
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Write counter of an entry, read from its raw account data.
+ *
+ * Meant for clients: fetch the account, read the version, and pass it to
+ * [`Mapping::update_versioned`](crate::Mapping::update_versioned) so the update
+ * fails if anyone wrote the entry in between.
+ *
+ * Returns `None` unless the data starts with an [`AccountHeader`] whose
+ * [`AccountHeader::WRITE_COUNTER`] flag is set.
+ */
+pub fn entry_version(data: &[u8]) -> Option<u64> {
+    let header: &AccountHeader = bytemuck::try_from_bytes(data.get(..HEADER_LEN)?).ok()?;
+    if !header.has_write_counter() {
+        return None;
+    }
+    let counter = data.get(HEADER_LEN..HEADER_LEN + WRITE_COUNTER_LEN)?;
+    Some(u64::from_le_bytes(counter.try_into().ok()?))
+}
+
+/**
+ * Offset of the value in entry data laid out with `header`: the header and,
+ * if flagged, the write counter.
+ */
+pub(crate) fn header_len(header: Option<AccountHeader>) -> usize {
+    match header {
+        Some(header) if header.has_write_counter() => HEADER_LEN + WRITE_COUNTER_LEN,
+        Some(_) => HEADER_LEN,
+        None => 0,
+    }
+}
+
+/**
+ * Validates the layout of entry data holding a `value_len`-byte value:
+ * length and, with a `header`, the stored header for `bump`.
+ */
+pub(crate) fn check_entry_data(
+    header: Option<AccountHeader>,
+    data: &[u8],
+    value_len: usize,
+    bump: u8,
+) -> Result<(), MappingError> {
+    if data.len() != header_len(header) + value_len {
+        return Err(MappingError::SizeMismatch);
+    }
+
+    if let Some(header) = header {
+        let stored: &AccountHeader = bytemuck::from_bytes(&data[..HEADER_LEN]);
+        header.with_bump(bump).check(stored)?;
+    }
+    Ok(())
+}
+
+/**
+ * Bump recorded in the header of entry data.
+ */
+pub(crate) fn stored_bump(data: &[u8]) -> Result<u8, MappingError> {
+    let stored: &AccountHeader = data
+        .get(..HEADER_LEN)
+        .map(bytemuck::from_bytes::<AccountHeader>)
+        .ok_or(MappingError::SizeMismatch)?;
+    Ok(stored.bump)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two value types of the same size, as `[u8; 8]` and `u64` fields would be.
+    struct Deposit;
+    struct Loan;
+    /// Next schema version of `Deposit`.
+    struct DepositV2;
+
+    impl MappingValue for Deposit {
+        const DISCRIMINATOR: [u8; 8] = discriminator("Deposit");
+        const VERSION: u8 = 1;
+    }
+
+    impl MappingValue for Loan {
+        const DISCRIMINATOR: [u8; 8] = discriminator("Loan");
+        const VERSION: u8 = 1;
+    }
+
+    impl MappingValue for DepositV2 {
+        const DISCRIMINATOR: [u8; 8] = discriminator("Deposit");
+        const VERSION: u8 = 2;
+    }
+
+    #[test]
+    fn discriminator_is_fnv1a_of_the_name() {
+        // FNV-1a offset basis for the empty string
+        assert_eq!(discriminator(""), 0xcbf2_9ce4_8422_2325u64.to_le_bytes());
+        assert_eq!(discriminator("Deposit"), Deposit::DISCRIMINATOR);
+        assert_ne!(discriminator("Deposit"), discriminator("Loan"));
+    }
+
+    #[test]
+    fn header_is_sixteen_bytes() {
+        assert_eq!(HEADER_LEN, 16);
+        assert_eq!(core::mem::align_of::<AccountHeader>(), 1);
+    }
+
+    #[test]
+    fn header_len_counts_the_write_counter() {
+        let header = AccountHeader::new::<Deposit>(0);
+        assert_eq!(header_len(None), 0);
+        assert_eq!(header_len(Some(header)), HEADER_LEN);
+        assert_eq!(
+            header_len(Some(header.with_flags(AccountHeader::WRITE_COUNTER))),
+            HEADER_LEN + WRITE_COUNTER_LEN
+        );
+    }
+
+    #[test]
+    fn check_entry_data_checks_length_and_header() {
+        let header = AccountHeader::new::<Deposit>(254);
+        let mut data = [0u8; HEADER_LEN + 8];
+        data[..HEADER_LEN].copy_from_slice(bytemuck::bytes_of(&header));
+
+        assert_eq!(check_entry_data(Some(header), &data, 8, 254), Ok(()));
+        assert_eq!(stored_bump(&data), Ok(254));
+        assert_eq!(
+            check_entry_data(Some(header), &data, 9, 254),
+            Err(MappingError::SizeMismatch)
+        );
+        assert_eq!(
+            check_entry_data(Some(AccountHeader::new::<Loan>(0)), &data, 8, 254),
+            Err(MappingError::DiscriminatorMismatch)
+        );
+        assert_eq!(check_entry_data(None, &data[HEADER_LEN..], 8, 254), Ok(()));
+        assert_eq!(stored_bump(&data[..4]), Err(MappingError::SizeMismatch));
+    }
+
+    /// Entry data with `header`, a counter of `count` and a 9-byte value.
+    fn entry(header: AccountHeader, count: u64) -> [u8; HEADER_LEN + WRITE_COUNTER_LEN + 9] {
+        let mut data = [0xaa; HEADER_LEN + WRITE_COUNTER_LEN + 9];
+        data[..HEADER_LEN].copy_from_slice(bytemuck::bytes_of(&header));
+        data[HEADER_LEN..HEADER_LEN + WRITE_COUNTER_LEN].copy_from_slice(&count.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn entry_version_reads_the_counter() {
+        let header = AccountHeader::default().with_flags(AccountHeader::WRITE_COUNTER);
+        assert_eq!(entry_version(&entry(header, 0)), Some(0));
+        assert_eq!(entry_version(&entry(header, 7)), Some(7));
+        assert_eq!(entry_version(&entry(header, u64::MAX)), Some(u64::MAX));
+
+        // Counter only: the value is not needed
+        assert_eq!(
+            entry_version(&entry(header, 7)[..HEADER_LEN + WRITE_COUNTER_LEN]),
+            Some(7)
+        );
+    }
+
+    #[test]
+    fn entry_version_needs_a_flagged_header_and_counter() {
+        let flagged = AccountHeader::new::<Deposit>(254).with_flags(AccountHeader::WRITE_COUNTER);
+
+        // No write counter flag
+        assert_eq!(
+            entry_version(&entry(AccountHeader::new::<Deposit>(254), 7)),
+            None
+        );
+        // Truncated counter or header
+        assert_eq!(entry_version(&entry(flagged, 7)[..HEADER_LEN + 4]), None);
+        assert_eq!(entry_version(&entry(flagged, 7)[..HEADER_LEN]), None);
+        assert_eq!(entry_version(&entry(flagged, 7)[..4]), None);
+        assert_eq!(entry_version(&[]), None);
+    }
+
+    #[test]
+    fn check_accepts_the_same_header() {
+        let stored = AccountHeader::new::<Deposit>(254);
+        assert_eq!(AccountHeader::new::<Deposit>(254).check(&stored), Ok(()));
+    }
+
+    #[test]
+    fn check_rejects_another_type() {
+        let stored = AccountHeader::new::<Loan>(254);
+        assert_eq!(
+            AccountHeader::new::<Deposit>(254).check(&stored),
+            Err(MappingError::DiscriminatorMismatch)
+        );
+    }
+
+    #[test]
+    fn check_rejects_another_version() {
+        let stored = AccountHeader::new::<Deposit>(254);
+        assert_eq!(
+            AccountHeader::new::<DepositV2>(254).check(&stored),
+            Err(MappingError::VersionMismatch)
+        );
+    }
+
+    #[test]
+    fn check_rejects_another_bump_or_layout() {
+        let stored = AccountHeader::new::<Deposit>(254);
+        assert_eq!(
+            AccountHeader::new::<Deposit>(253).check(&stored),
+            Err(MappingError::PdaMismatch)
+        );
+        assert_eq!(
+            AccountHeader::new::<Deposit>(254)
+                .with_flags(AccountHeader::WRITE_COUNTER)
+                .check(&stored),
+            Err(MappingError::FlagsMismatch)
+        );
+    }
+}
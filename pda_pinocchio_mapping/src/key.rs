@@ -0,0 +1,350 @@
+use pinocchio::{
+    instruction::Seed,
+    program_error::ProgramError,
+    pubkey::{try_find_program_address, Pubkey},
+};
+use pinocchio_pubkey::derive_address;
+
+use crate::MappingError;
+
+/**
+ * Maximum number of seeds a single key may expand to.
+ *
+ * A PDA accepts at most 16 seeds; the mapping name and the bump always use
+ * two of them, the rest is capped here to keep derivation cost bounded.
+ */
+pub const MAX_KEY_SEEDS: usize = 8;
+
+/**
+ * Seeds contributed by a mapping key, in derivation order.
+ *
+ * A key expanding to more than [`MAX_KEY_SEEDS`] seeds (e.g. nested tuples)
+ * does not panic: the extra seeds are dropped and the key is marked as
+ * overflowed, which every mapping operation reports as
+ * `ProgramError::InvalidSeeds`.
+ */
+#[derive(Clone, Copy)]
+pub struct KeySeeds<'k> {
+    seeds: [KeySeed<'k>; MAX_KEY_SEEDS],
+    len: usize,
+    overflowed: bool,
+}
+
+/**
+ * A single key seed: borrowed from the key, or the little-endian bytes of
+ * an integer key, which cannot be borrowed on every target.
+ */
+#[derive(Clone, Copy)]
+enum KeySeed<'k> {
+    Borrowed(&'k [u8]),
+    Int([u8; INT_SEED_LEN], usize),
+}
+
+/**
+ * Size of the largest integer key, `u128`.
+ */
+const INT_SEED_LEN: usize = 16;
+
+impl KeySeed<'_> {
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            KeySeed::Borrowed(seed) => seed,
+            KeySeed::Int(bytes, len) => &bytes[..*len],
+        }
+    }
+}
+
+impl<'k> KeySeeds<'k> {
+    pub const fn new() -> Self {
+        Self {
+            seeds: [KeySeed::Borrowed(&[]); MAX_KEY_SEEDS],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /**
+     * Appends `seed` after the seeds already collected.
+     *
+     * Past [`MAX_KEY_SEEDS`] seeds, `seed` is dropped and the key marked as
+     * overflowed, see [`KeySeeds::is_overflowed`].
+     */
+    pub fn push(self, seed: &'k [u8]) -> Self {
+        self.push_seed(KeySeed::Borrowed(seed))
+    }
+
+    /**
+     * Appends the little-endian bytes of an integer key, copied into the
+     * seeds so they do not depend on the target's byte order.
+     */
+    fn push_le_bytes<const N: usize>(self, le_bytes: [u8; N]) -> Self {
+        let mut bytes = [0u8; INT_SEED_LEN];
+        bytes[..N].copy_from_slice(&le_bytes);
+        self.push_seed(KeySeed::Int(bytes, N))
+    }
+
+    fn push_seed(mut self, seed: KeySeed<'k>) -> Self {
+        if self.len == MAX_KEY_SEEDS {
+            self.overflowed = true;
+            return self;
+        }
+        self.seeds[self.len] = seed;
+        self.len += 1;
+        self
+    }
+
+    /**
+     * Appends every seed of `other`, keeping their order.
+     */
+    pub fn extend(mut self, other: KeySeeds<'k>) -> Self {
+        for &seed in &other.seeds[..other.len] {
+            self = self.push_seed(seed);
+        }
+        self.overflowed |= other.overflowed;
+        self
+    }
+
+    /**
+     * Seeds collected so far, at most [`MAX_KEY_SEEDS`].
+     */
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.seeds[..self.len].iter().map(KeySeed::as_bytes)
+    }
+
+    /**
+     * Number of seeds collected so far, at most [`MAX_KEY_SEEDS`].
+     */
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /**
+     * Whether the key contributes no seed at all.
+     */
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /**
+     * Whether more than [`MAX_KEY_SEEDS`] seeds were pushed.
+     */
+    pub fn is_overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /**
+     * `ProgramError::InvalidSeeds` if the key does not fit.
+     */
+    pub(crate) fn check(&self) -> Result<(), ProgramError> {
+        if self.overflowed {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+}
+
+impl Default for KeySeeds<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * A value that can key a [`Mapping`](crate::Mapping).
+ *
+ * The key is turned into one or more PDA seeds which are placed between the
+ * mapping name and the bump: `[name, seeds.., bump]`.
+ *
+ * Provided implementations:
+ * - `Pubkey` and any `[u8; N]`, used as-is (seeds must not exceed 32 bytes),
+ * - unsigned and signed integers, as their little-endian bytes,
+ * - tuples of up to four keys, concatenating the seeds of each element.
+ *
+ * A key may expand to at most [`MAX_KEY_SEEDS`] seeds in total; operations
+ * on a larger key fail with `ProgramError::InvalidSeeds`.
+ *
+ * Usage:
+ * ```ignore
+ * shares.set(&(*maker.key(), *taker.key()), share, shares_account)?;
+ * positions.set(&(*owner.key(), nonce), position, position_account)?;
+ * ```
+ */
+pub trait MappingKey {
+    fn seeds(&self) -> KeySeeds<'_>;
+}
+
+impl<const N: usize> MappingKey for [u8; N] {
+    fn seeds(&self) -> KeySeeds<'_> {
+        KeySeeds::new().push(self.as_slice())
+    }
+}
+
+macro_rules! impl_mapping_key_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl MappingKey for $int {
+                fn seeds(&self) -> KeySeeds<'_> {
+                    KeySeeds::new().push_le_bytes(self.to_le_bytes())
+                }
+            }
+        )*
+    };
+}
+
+impl_mapping_key_for_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+macro_rules! impl_mapping_key_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: MappingKey),+> MappingKey for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn seeds(&self) -> KeySeeds<'_> {
+                let ($($name,)+) = self;
+                KeySeeds::new()$(.extend($name.seeds()))+
+            }
+        }
+    };
+}
+
+impl_mapping_key_for_tuple!(A);
+impl_mapping_key_for_tuple!(A, B);
+impl_mapping_key_for_tuple!(A, B, C);
+impl_mapping_key_for_tuple!(A, B, C, D);
+
+impl<K: MappingKey + ?Sized> MappingKey for &K {
+    fn seeds(&self) -> KeySeeds<'_> {
+        (**self).seeds()
+    }
+}
+
+/**
+ * Full seed list of a mapping entry: `[name, key seeds.., bump]`.
+ *
+ * Unused slots are empty slices, which do not change the derived address,
+ * so the whole array can be hashed with a fixed-size `derive_address`.
+ */
+pub(crate) struct PdaSeeds<'s> {
+    seeds: [&'s [u8]; MAX_KEY_SEEDS + 2],
+    len: usize,
+}
+
+impl<'s> PdaSeeds<'s> {
+    /**
+     * Fails with `ProgramError::InvalidSeeds` if `key` overflowed.
+     */
+    pub(crate) fn new(
+        name: &'s [u8],
+        key: &'s KeySeeds<'_>,
+        bump: &'s [u8; 1],
+    ) -> Result<Self, ProgramError> {
+        key.check()?;
+
+        let mut seeds: [&'s [u8]; MAX_KEY_SEEDS + 2] = [&[]; MAX_KEY_SEEDS + 2];
+        seeds[0] = name;
+        for (slot, seed) in seeds[1..].iter_mut().zip(key.iter()) {
+            *slot = seed;
+        }
+        seeds[key.len() + 1] = bump;
+
+        Ok(Self {
+            seeds,
+            len: key.len() + 2,
+        })
+    }
+
+    pub(crate) fn address(&self, program_id: &Pubkey) -> Pubkey {
+        derive_address(&self.seeds, None, program_id)
+    }
+
+    /**
+     * Seeds for `Signer::from(&seeds[..self.len()])`.
+     */
+    pub(crate) fn signer_seeds(&self) -> [Seed<'s>; MAX_KEY_SEEDS + 2] {
+        self.seeds.map(Seed::from)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/**
+ * Canonical PDA and bump of `[name, key seeds..]` under `program_id`,
+ * shared by [`Mapping::find`](crate::Mapping::find) and
+ * [`ForeignMapping::find`](crate::ForeignMapping::find).
+ */
+pub(crate) fn find_address<K: MappingKey + ?Sized>(
+    program_id: &Pubkey,
+    name: &[u8],
+    key: &K,
+) -> Result<(Pubkey, u8), ProgramError> {
+    let key_seeds = key.seeds();
+    key_seeds.check()?;
+
+    let mut seeds: [&[u8]; MAX_KEY_SEEDS + 1] = [&[]; MAX_KEY_SEEDS + 1];
+    seeds[0] = name;
+    for (slot, seed) in seeds[1..].iter_mut().zip(key_seeds.iter()) {
+        *slot = seed;
+    }
+
+    try_find_program_address(&seeds[..key_seeds.len() + 1], program_id)
+        .ok_or(ProgramError::InvalidSeeds)
+}
+
+/**
+ * Fails with `MappingError::PdaMismatch` unless `address` is the PDA derived
+ * from `[name, key seeds.., bump]` under `program_id`.
+ */
+pub(crate) fn check_address<K: MappingKey + ?Sized>(
+    program_id: &Pubkey,
+    name: &[u8],
+    key: &K,
+    bump: u8,
+    address: &Pubkey,
+) -> Result<(), ProgramError> {
+    let bump = [bump];
+    let key_seeds = key.seeds();
+    let seeds = PdaSeeds::new(name, &key_seeds, &bump)?;
+
+    if seeds.address(program_id) != *address {
+        return Err(MappingError::PdaMismatch.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuple_keys_concatenate_seeds() {
+        let key = ([1u8; 32], 7u64, (2u8, 3u16));
+        let seeds = key.seeds();
+        assert_eq!(seeds.len(), 4);
+        assert_eq!(seeds.iter().nth(1), Some(7u64.to_le_bytes().as_slice()));
+        assert!(!seeds.is_overflowed());
+    }
+
+    #[test]
+    fn integer_keys_seed_little_endian_bytes() {
+        let key = 0x0102_0304u32;
+        assert_eq!(key.seeds().iter().next(), Some([4u8, 3, 2, 1].as_slice()));
+
+        let key = -2i128;
+        assert_eq!(
+            key.seeds().iter().next(),
+            Some((-2i128).to_le_bytes().as_slice())
+        );
+    }
+
+    #[test]
+    fn oversized_keys_overflow_instead_of_panicking() {
+        let key = ((1u8, 2u8, 3u8), (4u8, 5u8, 6u8), (7u8, 8u8, 9u8));
+        let seeds = key.seeds();
+        assert!(seeds.is_overflowed());
+        assert_eq!(seeds.len(), MAX_KEY_SEEDS);
+        assert_eq!(seeds.check().err(), Some(ProgramError::InvalidSeeds));
+
+        let bump = [255];
+        assert!(PdaSeeds::new(b"name", &seeds, &bump).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+/*!
+ * Conversions between the value part of an entry's data and `T`.
+ *
+ * Account data has no alignment guarantee towards `T`: it sits behind an
+ * optional header, and `T` may need more alignment than the runtime gives
+ * (e.g. `u128` fields), or none at all (`[u8; N]` fields, `#[repr(packed)]`).
+ * References are only handed out when the bytes are aligned for `T`; copies
+ * work at any address.
+ */
+
+use bytemuck::{
+    checked::{self, CheckedCastError},
+    CheckedBitPattern, NoUninit, Pod, PodCastError,
+};
+
+use pinocchio::program_error::ProgramError;
+
+use crate::MappingError;
+
+/**
+ * Borrows `bytes` as a `T`.
+ *
+ * Fails with `SizeMismatch` unless `bytes` holds exactly one `T`, and with
+ * `Misaligned` unless it starts at a multiple of `align_of::<T>()`.
+ */
+pub(crate) fn value_ref<T: Pod>(bytes: &[u8]) -> Result<&T, MappingError> {
+    check_layout::<T>(bytes)?;
+    Ok(bytemuck::from_bytes(bytes))
+}
+
+/**
+ * Mutably borrows `bytes` as a `T`, with the checks of [`value_ref`].
+ */
+pub(crate) fn value_mut<T: Pod>(bytes: &mut [u8]) -> Result<&mut T, MappingError> {
+    check_layout::<T>(bytes)?;
+    Ok(bytemuck::from_bytes_mut(bytes))
+}
+
+/**
+ * Copies a `T` out of `bytes`, whatever their alignment.
+ */
+pub(crate) fn read_value<T: Pod>(bytes: &[u8]) -> Result<T, MappingError> {
+    if bytes.len() != core::mem::size_of::<T>() {
+        return Err(MappingError::SizeMismatch);
+    }
+    Ok(bytemuck::pod_read_unaligned(bytes))
+}
+
+/**
+ * Copies `value` into `bytes`, whatever their alignment.
+ */
+pub(crate) fn write_value<T: NoUninit>(bytes: &mut [u8], value: &T) -> Result<(), MappingError> {
+    if bytes.len() != core::mem::size_of::<T>() {
+        return Err(MappingError::SizeMismatch);
+    }
+    bytes.copy_from_slice(bytemuck::bytes_of(value));
+    Ok(())
+}
+
+/**
+ * Borrows `bytes` as a `T` after checking they hold a valid bit pattern.
+ *
+ * Fails like [`value_ref`], and with `ProgramError::InvalidAccountData`
+ * when e.g. a `bool` byte is neither 0 nor 1, or an enum tag is out of range.
+ */
+pub(crate) fn checked_ref<T: CheckedBitPattern>(bytes: &[u8]) -> Result<&T, ProgramError> {
+    checked::try_from_bytes(bytes).map_err(checked_error)
+}
+
+/**
+ * Copies a `T` out of `bytes`, whatever their alignment, after checking
+ * they hold a valid bit pattern.
+ */
+pub(crate) fn checked_read<T: CheckedBitPattern>(bytes: &[u8]) -> Result<T, ProgramError> {
+    checked::try_pod_read_unaligned(bytes).map_err(checked_error)
+}
+
+fn checked_error(e: CheckedCastError) -> ProgramError {
+    match e {
+        CheckedCastError::InvalidBitPattern => ProgramError::InvalidAccountData,
+        CheckedCastError::PodCastError(PodCastError::TargetAlignmentGreaterAndInputNotAligned) => {
+            MappingError::Misaligned.into()
+        }
+        CheckedCastError::PodCastError(_) => MappingError::SizeMismatch.into(),
+    }
+}
+
+/**
+ * Fails unless `bytes` can be viewed in place as a `T`.
+ */
+fn check_layout<T: Pod>(bytes: &[u8]) -> Result<(), MappingError> {
+    if bytes.len() != core::mem::size_of::<T>() {
+        return Err(MappingError::SizeMismatch);
+    }
+    if (bytes.as_ptr() as usize) % core::mem::align_of::<T>() != 0 {
+        return Err(MappingError::Misaligned);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    /// 8-byte aligned value, as with plain `u64` fields.
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+    struct Wide {
+        amount: u64,
+        bump: u8,
+        _padding: [u8; 7],
+    }
+
+    /// Alignment-1 value, as the escrow `Share`.
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+    struct Bytes {
+        amount: [u8; 8],
+        bump: u8,
+    }
+
+    /// Packed value, `u64` field without its alignment.
+    #[repr(C, packed)]
+    #[derive(Clone, Copy, Pod, Zeroable)]
+    struct Packed {
+        amount: u64,
+        bump: u8,
+    }
+
+    /// Backing storage whose start is 16-byte aligned, sliced at an offset
+    /// to get deliberately (mis)aligned value bytes.
+    #[repr(C, align(16))]
+    struct Buffer([u8; 64]);
+
+    impl Buffer {
+        fn new() -> Self {
+            Self([0; 64])
+        }
+
+        fn at<T>(&mut self, offset: usize) -> &mut [u8] {
+            &mut self.0[offset..offset + core::mem::size_of::<T>()]
+        }
+    }
+
+    const WIDE: Wide = Wide {
+        amount: 0x0102_0304_0506_0708,
+        bump: 254,
+        _padding: [0; 7],
+    };
+
+    #[test]
+    fn value_ref_accepts_aligned_bytes() {
+        let mut buffer = Buffer::new();
+        for offset in [0, 8, 16] {
+            write_value(buffer.at::<Wide>(offset), &WIDE).unwrap();
+            assert_eq!(value_ref::<Wide>(buffer.at::<Wide>(offset)), Ok(&WIDE));
+        }
+    }
+
+    #[test]
+    fn value_ref_rejects_misaligned_bytes() {
+        let mut buffer = Buffer::new();
+        for offset in 1..8 {
+            assert_eq!(
+                value_ref::<Wide>(buffer.at::<Wide>(offset)),
+                Err(MappingError::Misaligned)
+            );
+            assert_eq!(
+                value_mut::<Wide>(buffer.at::<Wide>(offset)).err(),
+                Some(MappingError::Misaligned)
+            );
+        }
+    }
+
+    #[test]
+    fn value_ref_accepts_alignment_one_values_at_any_offset() {
+        let value = Bytes {
+            amount: 42u64.to_le_bytes(),
+            bump: 255,
+        };
+        let mut buffer = Buffer::new();
+        for offset in 0..16 {
+            write_value(buffer.at::<Bytes>(offset), &value).unwrap();
+            assert_eq!(value_ref::<Bytes>(buffer.at::<Bytes>(offset)), Ok(&value));
+        }
+    }
+
+    #[test]
+    fn read_and_write_work_on_misaligned_bytes() {
+        let mut buffer = Buffer::new();
+        for offset in 1..8 {
+            write_value(buffer.at::<Wide>(offset), &WIDE).unwrap();
+            assert_eq!(read_value::<Wide>(buffer.at::<Wide>(offset)), Ok(WIDE));
+        }
+    }
+
+    #[test]
+    fn packed_values_round_trip_at_any_offset() {
+        let mut buffer = Buffer::new();
+        for offset in 0..16 {
+            let value = Packed {
+                amount: u64::MAX - offset as u64,
+                bump: offset as u8,
+            };
+            write_value(buffer.at::<Packed>(offset), &value).unwrap();
+
+            let read: Packed = read_value(buffer.at::<Packed>(offset)).unwrap();
+            assert_eq!({ read.amount }, u64::MAX - offset as u64);
+            assert_eq!(read.bump, offset as u8);
+
+            // Alignment 1: also viewable in place
+            assert!(value_ref::<Packed>(buffer.at::<Packed>(offset)).is_ok());
+        }
+    }
+
+    #[test]
+    fn wrong_lengths_are_size_mismatches() {
+        let mut buffer = Buffer::new();
+        let bytes = &mut buffer.0[..core::mem::size_of::<Wide>() + 1];
+
+        assert_eq!(value_ref::<Wide>(bytes), Err(MappingError::SizeMismatch));
+        assert_eq!(read_value::<Wide>(bytes), Err(MappingError::SizeMismatch));
+        assert_eq!(write_value(bytes, &WIDE), Err(MappingError::SizeMismatch));
+        assert_eq!(read_value::<Wide>(&[]), Err(MappingError::SizeMismatch));
+    }
+
+    /// Value with a flag and a status enum, not `Pod`.
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, PartialEq, CheckedBitPattern, NoUninit)]
+    struct Flagged {
+        active: bool,
+        status: Status,
+        bump: u8,
+    }
+
+    #[repr(u8)]
+    #[derive(Clone, Copy, Debug, PartialEq, CheckedBitPattern, NoUninit)]
+    enum Status {
+        Open = 0,
+        Settled = 1,
+    }
+
+    #[test]
+    fn checked_values_round_trip() {
+        let value = Flagged {
+            active: true,
+            status: Status::Settled,
+            bump: 253,
+        };
+        let mut buffer = Buffer::new();
+        for offset in 0..8 {
+            write_value(buffer.at::<Flagged>(offset), &value).unwrap();
+            assert_eq!(
+                checked_ref::<Flagged>(buffer.at::<Flagged>(offset)),
+                Ok(&value)
+            );
+            assert_eq!(
+                checked_read::<Flagged>(buffer.at::<Flagged>(offset)),
+                Ok(value)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_bit_patterns_are_rejected() {
+        let mut buffer = Buffer::new();
+
+        // `active` is neither 0 nor 1
+        buffer.at::<Flagged>(0).copy_from_slice(&[2, 0, 255]);
+        assert_eq!(
+            checked_ref::<Flagged>(buffer.at::<Flagged>(0)),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        // `status` out of range
+        buffer.at::<Flagged>(0).copy_from_slice(&[1, 2, 255]);
+        assert_eq!(
+            checked_read::<Flagged>(buffer.at::<Flagged>(0)),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        assert_eq!(
+            checked_read::<Flagged>(&[1, 1]),
+            Err(MappingError::SizeMismatch.into())
+        );
+    }
+}
@@ -1,15 +1,55 @@
 #![no_std]
-use bytemuck::Pod;
+#[cfg(feature = "std")]
+extern crate std;
+
+use bytemuck::{NoUninit, Pod};
+use core::marker::PhantomData;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::{
-    account_info::AccountInfo,
-    instruction::{Seed, Signer},
+    account_info::{AccountInfo, Ref, RefMut},
+    instruction::Signer,
     program_error::ProgramError,
     sysvars::{rent::Rent, Sysvar},
     ProgramResult,
 };
-use pinocchio_pubkey::derive_address;
-use pinocchio_system::instructions::CreateAccount;
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
+
+mod batch;
+mod checked;
+mod context;
+mod double;
+mod entry;
+mod error;
+mod foreign;
+mod header;
+mod key;
+mod layout;
+mod pod;
+#[cfg(feature = "borsh")]
+mod serialized;
+mod signer;
+mod slice;
+mod vault;
+mod versioned;
+
+pub use batch::BatchError;
+pub use context::MappingContext;
+pub use double::DoubleMapping;
+pub use error::MappingError;
+pub use foreign::ForeignMapping;
+pub use header::{
+    discriminator, entry_version, AccountHeader, MappingValue, HEADER_LEN, WRITE_COUNTER_LEN,
+};
+pub use key::{KeySeeds, MappingKey, MAX_KEY_SEEDS};
+#[cfg(feature = "derive")]
+pub use pda_pinocchio_mapping_derive::{mapping_value, Bumpy};
+pub use pod::{PodBool, PodI64, PodU128, PodU16, PodU32, PodU64};
+#[cfg(feature = "borsh")]
+pub use serialized::BorshValue;
+pub use signer::TokenTransfer;
+pub use vault::VaultMapping;
+
+use key::{check_address, find_address, PdaSeeds};
 
 /**
  */
@@ -18,32 +58,334 @@ pub trait Bumpy {
     fn bump(&self) -> u8;
 }
 
-pub struct Mapping<'a> {
+/**
+ * A named mapping from keys to values of type `T`, each entry stored in its
+ * own PDA account.
+ *
+ * The value type is fixed when the mapping is declared, so every entry under
+ * `name` is read and written as the same `T`.
+ *
+ * A `Mapping` is a handful of references and flags, and is `Copy`: the same
+ * handle can serve any number of calls within an instruction.
+ */
+pub struct Mapping<'a, T: ?Sized> {
     pub program_id: &'a Pubkey,
     pub name: &'static [u8],
     pub payer: &'a AccountInfo,
+    pub(crate) header: Option<AccountHeader>,
+    pub(crate) strict: bool,
+    pub(crate) rent: Option<&'a Rent>,
+    _value: PhantomData<T>,
+}
+
+impl<T: ?Sized> Clone for Mapping<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
-impl<'a> Mapping<'a> {
+impl<T: ?Sized> Copy for Mapping<'_, T> {}
+
+impl<'a, T: ?Sized> Mapping<'a, T> {
     pub fn new(program_id: &'a Pubkey, name: &'static [u8], payer: &'a AccountInfo) -> Self {
         Self {
             program_id,
             name,
             payer,
+            header: None,
+            strict: false,
+            rent: None,
+            _value: PhantomData,
         }
     }
 
+    /**
+     * Uses `rent` for every rent computation instead of reading the Rent
+     * sysvar on each account creation or resize.
+     *
+     * Usually set through [`MappingContext`], which fetches the sysvar once
+     * per instruction.
+     */
+    pub fn with_rent(mut self, rent: &'a Rent) -> Self {
+        self.rent = Some(rent);
+        self
+    }
+
+    /**
+     * Only accepts canonical bumps when creating entries.
+     *
+     * Any bump that derives a valid PDA is accepted by default, so up to
+     * 255 distinct entries could exist for the same `(name, key)`. In strict
+     * mode every creation (`create`, `set`, `create_entry`, `set_entry`,
+     * `set_slice`) first runs [`Mapping::find`] and fails with
+     * `MappingError::NonCanonicalBump` unless the bump is the canonical one,
+     * which restores the one-entry-per-key guarantee.
+     *
+     * Existing entries are not re-checked on overwrite: they went through the
+     * same check when they were created. The extra `find_program_address`
+     * costs compute units on every creation.
+     */
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /**
+     * Closes the PDA account associated with `(name, key)` and refunds its rent.
+     *
+     * This method derives the PDA using:
+     *   - the mapping's static `name`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
+     *   - the provided `bump`.
+     *
+     * Behavior:
+     * - Verifies that the passed `account` matches the derived PDA.
+     * - Fails if the PDA account is not initialized or deriveable by `program_id`.
+     * - Zeroes the account data and shrinks it to zero length.
+     * - Moves every lamport held by `account` into `recipient`.
+     * - Assigns the account back to the system program, so the entry cannot be
+     *   written again by `update` within the same transaction.
+     *
+     * Requirements:
+     * - The account must already be created and owned by `program_id`.
+     * - `account` and `recipient` must be writable.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` if the entry is closed.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `ProgramError::ArithmeticOverflow` if `recipient` lamports would overflow.
+     */
+    pub fn remove<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+        recipient: &AccountInfo,
+    ) -> ProgramResult {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        self.close_account(account, recipient)
+    }
+
+    /**
+     * Zeroes and closes an entry account whose address and owner are
+     * checked, moving its lamports into `recipient`.
+     */
+    fn close_account(&self, account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+        {
+            let mut data = account.try_borrow_mut_data()?;
+            data.fill(0);
+        }
+
+        {
+            let mut account_lamports = account.try_borrow_mut_lamports()?;
+            let mut recipient_lamports = recipient.try_borrow_mut_lamports()?;
+            *recipient_lamports = recipient_lamports
+                .checked_add(*account_lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            *account_lamports = 0;
+        }
+
+        account.resize(0)?;
+        unsafe { account.assign(&pinocchio_system::ID) };
+        Ok(())
+    }
+
+    /**
+     * Returns the canonical PDA and bump of the entry associated with `(name, key)`.
+     *
+     * Runs `find_program_address` over `[name, key seeds..]`, i.e. it searches
+     * the highest bump giving an off-curve address. This is the bump clients
+     * get from `Pubkey::find_program_address` with the same seeds.
+     *
+     * Only available on-chain; off-chain it returns `ProgramError::InvalidSeeds`.
+     *
+     * Returns:
+     * - `Ok((address, bump))` of the canonical PDA.
+     * - `ProgramError::InvalidSeeds` if no bump yields a valid PDA, or the
+     *   key has more than [`MAX_KEY_SEEDS`] seeds.
+     */
+    pub fn find<K: MappingKey + ?Sized>(&self, key: &K) -> Result<(Pubkey, u8), ProgramError> {
+        find_address(self.program_id, self.name, key)
+    }
+
+    /**
+     * Fails unless `account` is the PDA derived from `[name, key, bump]`.
+     */
+    fn check_address<K: MappingKey + ?Sized>(
+        &self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        check_address(self.program_id, self.name, key, bump, account.key())
+    }
+
+    /**
+     * Fails unless `account` is an existing entry owned by `program_id`.
+     *
+     * Accounts still held by the system program are reported as
+     * uninitialized, any other owner as a wrong owner.
+     */
+    fn check_owner(&self, account: &AccountInfo) -> ProgramResult {
+        check_owner(self.program_id, account)
+    }
+
+    /**
+     * Creates the PDA account for `[name, key, bump]` with `space` bytes,
+     * funded by `payer` and owned by `program_id`.
+     *
+     * `CreateAccount` fails on an address that already holds lamports, which
+     * would let anyone block an entry by sending it a single lamport. In that
+     * case the account is set up in three steps instead:
+     * - `Transfer` of the rent still missing, if any, from `payer`,
+     * - `Allocate` of `space` bytes, signed by the PDA,
+     * - `Assign` to `program_id`, signed by the PDA.
+     *
+     * In strict mode, fails with `MappingError::NonCanonicalBump` before
+     * anything is created unless `bump` is canonical.
+     */
+    fn create_account<K: MappingKey + ?Sized>(
+        &self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+        space: usize,
+    ) -> ProgramResult {
+        if self.strict && self.find(key)?.1 != bump {
+            return Err(MappingError::NonCanonicalBump.into());
+        }
+
+        let bump = [bump];
+        let key_seeds = key.seeds();
+        let seeds = PdaSeeds::new(self.name, &key_seeds, &bump)?;
+        let signer_seeds = seeds.signer_seeds();
+        let signer = Signer::from(&signer_seeds[..seeds.len()]);
+
+        let lamports = self.minimum_balance(space)?;
+
+        if account.lamports() == 0 {
+            return CreateAccount {
+                from: self.payer,
+                to: account,
+                lamports,
+                space: space as u64,
+                owner: self.program_id,
+            }
+            .invoke_signed(&[signer]);
+        }
+
+        // Pre-funded PDA - top up the rent and take it over
+        let missing = lamports.saturating_sub(account.lamports());
+        if missing > 0 {
+            Transfer {
+                from: self.payer,
+                to: account,
+                lamports: missing,
+            }
+            .invoke()?;
+        }
+
+        Allocate {
+            account,
+            space: space as u64,
+        }
+        .invoke_signed(&[signer.clone()])?;
+
+        Assign {
+            account,
+            owner: self.program_id,
+        }
+        .invoke_signed(&[signer])
+    }
+
+    /**
+     * Resizes an existing entry account to `new_len` bytes, keeping it
+     * rent-exempt.
+     *
+     * - Growing transfers the missing rent from `payer`.
+     * - Shrinking moves the lamports above the new rent-exempt minimum
+     *   back to `payer`.
+     *
+     * A single instruction can grow an account by at most 10 KiB.
+     */
+    fn resize_account(&self, account: &AccountInfo, new_len: usize) -> ProgramResult {
+        let required = self.minimum_balance(new_len)?;
+        let current = account.lamports();
+
+        if required > current {
+            Transfer {
+                from: self.payer,
+                to: account,
+                lamports: required - current,
+            }
+            .invoke()?;
+        } else if current > required {
+            let excess = current - required;
+            *account.try_borrow_mut_lamports()? -= excess;
+            let mut payer_lamports = self.payer.try_borrow_mut_lamports()?;
+            *payer_lamports = payer_lamports
+                .checked_add(excess)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        account.resize(new_len)
+    }
+
+    /**
+     * Rent-exempt minimum of an account holding `space` bytes, from the
+     * cached rent if any, else from the Rent sysvar.
+     */
+    fn minimum_balance(&self, space: usize) -> Result<u64, ProgramError> {
+        match self.rent {
+            Some(rent) => Ok(rent.minimum_balance(space)),
+            None => Ok(Rent::get()?.minimum_balance(space)),
+        }
+    }
+}
+
+impl<'a, T: Pod> Mapping<'a, T> {
+    /**
+     * Stores an [`AccountHeader`] in front of every value of this mapping.
+     *
+     * Behavior:
+     * - `create` and `set` write the header when they create an entry.
+     * - `set`, `update`, `get` and `get_mut` check that the stored
+     *   discriminator, version and bump match `T` and the entry, so an
+     *   account holding another type of the same size is rejected.
+     * - Entries are `HEADER_LEN` bytes larger; clients can filter accounts
+     *   with a memcmp on `T::DISCRIMINATOR` at offset 0.
+     *
+     * A mapping must always be used with or always without header, the two
+     * layouts are not interchangeable.
+     */
+    pub fn with_header(mut self) -> Self
+    where
+        T: MappingValue,
+    {
+        let flags = self.header.map_or(0, |header| header.flags);
+        self.header = Some(AccountHeader::new::<T>(0).with_flags(flags));
+        self
+    }
+}
+
+impl<'a, T: Pod + Bumpy> Mapping<'a, T> {
     /** Writes a value into the PDA account associated with `(name, key)`.
      *
      * This method derives the PDA using:
      *   - the mapping's static `name`,
-     *   - the provided `key`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
      *   - the bump extracted from `value`.
      *
      * Behavior:
      * - If the PDA account does not exist but deriveable by `program_id`,
      *   it is created with the required space and rent-exempt balance,
      *   then initialized with `value`.
+     *   Lamports already sent to the PDA address are kept, `payer` only
+     *   covers the missing rent.
      *
      * - If the PDA account already exists and is owned by `program_id`,
      *   its contents are overwritten with `value`.
@@ -51,7 +393,7 @@ impl<'a> Mapping<'a> {
      * Safety & validation:
      * - Ensures the passed `account` matches the derived PDA.
      * - Ensures the account's data length matches `T::LEN`.
-     * - Ensures the memory alignment is valid for bytemuck casting.
+     * - Copies the bytes of `value`, the data needs no alignment for `T`.
      *
      * Requirements:
      * - `T` must implement `Sizeable` (exposes `LEN`) and `Bumpy` (exposes `bump()`).
@@ -59,63 +401,28 @@ impl<'a> Mapping<'a> {
      *
      * Returns:
      * - `ProgramResult::Ok(())` on success.
-     * - `ProgramError` if account mismatch, invalid data, or system-instruction
-     *   failures occur.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` for invalid data.
+     * - `ProgramError` if system-instruction failures occur.
      */
-    pub fn set<T: Pod + Bumpy>(
+    pub fn set<K: MappingKey + ?Sized>(
         self,
-        key: &Pubkey,
+        key: &K,
         value: T,
         account: &AccountInfo,
     ) -> ProgramResult {
-        let size_T = core::mem::size_of::<T>();
-        let seed = [self.name.as_ref(), key.as_slice(), &[value.bump()]];
-
-        let account_pda = derive_address(&seed, None, self.program_id);
-        assert_eq!(account_pda, *account.key(), "Mapping: Accounts Mismatching");
-        let bump = [value.bump().to_le()];
-        let seed = [
-            Seed::from(self.name.as_ref()),
-            Seed::from(key.as_slice()),
-            Seed::from(&bump),
-        ];
-        let seeds = Signer::from(&seed);
+        self.check_address(key, value.bump(), account)?;
 
         if account.owner() != self.program_id {
-            CreateAccount {
-                from: self.payer,
-                to: account,
-                lamports: Rent::get()?.minimum_balance(size_T),
-                space: size_T as u64,
-                owner: self.program_id,
-            }
-            .invoke_signed(&[seeds.clone()])?;
-
-            let mut data = account.try_borrow_mut_data()?;
-            if data.len() != size_T {
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-                return Err(ProgramError::InvalidAccountData);
+            if account.owner() != &pinocchio_system::ID {
+                return Err(MappingError::WrongOwner.into());
             }
-            let t_ref: &mut T = bytemuck::from_bytes_mut(&mut data);
-            *t_ref = value;
-            Ok(())
-        } else {
-            // Account already exists - overwrite
-            let mut data = account.try_borrow_mut_data()?;
-            if data.len() != size_T {
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            let t_ref: &mut T = bytemuck::from_bytes_mut(&mut data);
-            *t_ref = value;
-            Ok(())
+            self.create_account(key, value.bump(), account, self.data_len())?;
+            self.init_header(account, value.bump())?;
         }
+        // Account exists now - (over)write
+        self.write_value(account, value.bump(), value)
     }
 
     /**
@@ -123,7 +430,7 @@ impl<'a> Mapping<'a> {
      *
      * This method derives the PDA using:
      *   - the mapping's static `name`,
-     *   - the provided `key`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
      *   - the bump extracted from `value`.
      *
      * Behavior:
@@ -133,8 +440,7 @@ impl<'a> Mapping<'a> {
      *
      * Safety & validation:
      * - Ensures the account's data length matches `T::LEN`.
-     * - Ensures proper memory alignment for bytemuck casting.
-     * - Casts raw account data into `T` and assigns the new `value`.
+     * - Copies the bytes of `value`, the data needs no alignment for `T`.
      *
      * Requirements:
      * - `T` must implement `Sizeable` (defines `LEN`) and `Bumpy` (defines `bump()`).
@@ -143,37 +449,22 @@ impl<'a> Mapping<'a> {
      *
      * Returns:
      * - `ProgramResult::Ok(())` if the value is successfully written.
-     * - `ProgramError::UninitializedAccount` if the PDA does not exist or is not owned.
-     * - `ProgramError::InvalidAccountData` if the stored data layout does not match `T`.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the stored data length does not match `T`.
      */
-    pub fn update<T: Pod + Bumpy>(
+    pub fn update<K: MappingKey + ?Sized>(
         self,
-        key: &Pubkey,
+        key: &K,
         value: T,
         account: &AccountInfo,
     ) -> ProgramResult {
-        let size_T = core::mem::size_of::<T>();
-        let seed = [self.name.as_ref(), key.as_slice(), &[value.bump()]];
+        self.check_address(key, value.bump(), account)?;
+        self.check_owner(account)?;
 
-        let account_pda = derive_address(&seed, None, self.program_id);
-        assert_eq!(account_pda, *account.key(), "Mapping: Accounts Mismatching");
-
-        if account.owner() != self.program_id {
-            Err(ProgramError::UninitializedAccount)
-        } else {
-            // Account already exists - overwrite
-            let mut data = account.try_borrow_mut_data()?;
-            if data.len() != size_T {
-                return Err(ProgramError::InvalidAccountData);
-            }
-
-            if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            let t_ref: &mut T = bytemuck::from_bytes_mut(&mut data);
-            *t_ref = value;
-            Ok(())
-        }
+        // Account already exists - overwrite
+        self.write_value(account, value.bump(), value)
     }
 
     /**
@@ -181,19 +472,20 @@ impl<'a> Mapping<'a> {
      *
      * This method derives the PDA using:
      *   - the mapping's static `name`,
-     *   - the provided `key`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
      *   - the bump extracted from `value`.
      *
      * Behavior:
      * - Verifies that the passed `account` matches the derived PDA.
+     * - Creates the account, keeping any lamports already sent to the PDA
+     *   address and charging `payer` only for the missing rent.
      * - After creation, the account's data buffer is initialized with `value`.
      * - If the account already exists, the operation
      *   fails, as `create()` is intended for first-time initialization only.
      *
      * Safety & validation:
      * - Ensures account data length matches `T::LEN`.
-     * - Ensures proper alignment for bytemuck casting into `T`.
-     * - Writes the provided `value` directly into account data.
+     * - Copies the bytes of `value`, the data needs no alignment for `T`.
      *
      * Requirements:
      * - `T` must implement `Sizeable` (defines `LEN`) and `Bumpy` (defines `bump()`).
@@ -201,54 +493,571 @@ impl<'a> Mapping<'a> {
      *
      * Returns:
      * - `ProgramResult::Ok(())` if the PDA is successfully created and initialized.
-     * - `ProgramError::InvalidAccountData` for size or alignment mismatches.
-     * - `ProgramError::IllegalOwner` if the PDA already exists and is owned.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::SizeMismatch` for size mismatches.
+     * - `MappingError::AlreadyInitialized` if the PDA already exists and is owned.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
      * - Propagated errors from system account creation or rent retrieval.
      */
 
-    pub fn create<T: Pod + Bumpy>(
+    pub fn create<K: MappingKey + ?Sized>(
         self,
-        key: &Pubkey,
+        key: &K,
         value: T,
         account: &AccountInfo,
     ) -> ProgramResult {
-        let size_T = core::mem::size_of::<T>();
-        let seed = [self.name.as_ref(), key.as_slice(), &[value.bump()]];
-
-        let account_pda = derive_address(&seed, None, self.program_id);
-        assert_eq!(account_pda, *account.key(), "Mapping: Accounts Mismatching");
-        let bump = [value.bump().to_le()];
-        let seed = [
-            Seed::from(self.name.as_ref()),
-            Seed::from(key.as_slice()),
-            Seed::from(&bump),
-        ];
-        let seeds = Signer::from(&seed);
+        self.check_address(key, value.bump(), account)?;
 
-        if account.owner() != self.program_id {
-            CreateAccount {
-                from: self.payer,
-                to: account,
-                lamports: Rent::get()?.minimum_balance(size_T),
-                space: size_T as u64,
-                owner: self.program_id,
+        if account.owner() == self.program_id {
+            return Err(MappingError::AlreadyInitialized.into());
+        }
+        if account.owner() != &pinocchio_system::ID {
+            return Err(MappingError::WrongOwner.into());
+        }
+
+        self.create_account(key, value.bump(), account, self.data_len())?;
+        self.init_header(account, value.bump())?;
+        self.write_value(account, value.bump(), value)
+    }
+
+    /**
+     * Reads the value stored in the PDA account associated with `(name, key)`.
+     *
+     * This method derives the PDA using:
+     *   - the mapping's static `name`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
+     *   - the provided `bump`.
+     *
+     * Behavior:
+     * - Verifies that the passed `account` matches the derived PDA.
+     * - Fails if the PDA account is not initialized or deriveable by `program_id`.
+     * - Returns a `Ref` guard into the account data, no copy is made.
+     * - The account data stays borrowed until the guard is dropped, so
+     *   `get_mut`, `update` or `remove` on the same account fail meanwhile.
+     *
+     * Safety & validation:
+     * - Ensures the account's data length matches `size_of::<T>()`.
+     * - Ensures the value is aligned for `T`, see [`Mapping::read`] for
+     *   types the account data cannot satisfy.
+     * - Ensures the bump stored in the value matches `bump`.
+     *
+     * Requirements:
+     * - `T` must implement `Pod` and `Bumpy` (defines `bump()`).
+     * - The account must already be created and owned by `program_id`.
+     *
+     * Returns:
+     * - `Ok(Ref<T>)` over the stored value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` or `MappingError::Misaligned` if the stored
+     *   data layout does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     * - `ProgramError::AccountBorrowFailed` if the data is mutably borrowed.
+     */
+    pub fn get<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, T>, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        let t_ref: &T = layout::value_ref(&data[header_len..])?;
+        if t_ref.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Ref::map(data, |data| {
+            bytemuck::from_bytes::<T>(&data[header_len..])
+        }))
+    }
+
+    /**
+     * Copies the value stored in the PDA account associated with `(name, key)`.
+     *
+     * Runs the same derivation and validation as [`Mapping::get`], except
+     * for alignment: the value is read with `bytemuck::pod_read_unaligned`,
+     * so this works for `#[repr(packed)]` types and for types whose alignment
+     * the account data does not satisfy (e.g. `u128` fields). Write the
+     * changed copy back with [`Mapping::update`], which needs no alignment
+     * either.
+     *
+     * Returns:
+     * - `Ok(T)`, a copy of the stored value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the stored data length does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     */
+    pub fn read<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> Result<T, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        let value: T = layout::read_value(&data[self.header_len()..])?;
+        if value.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(value)
+    }
+
+    /**
+     * Mutably borrows the value stored in the PDA account associated with `(name, key)`.
+     *
+     * Runs the same derivation and validation as [`Mapping::get`], but returns
+     * a `RefMut` guard over the account data so the value can be edited in place.
+     *
+     * Behavior:
+     * - Verifies that the passed `account` matches the derived PDA.
+     * - Fails if the PDA account is not initialized or deriveable by `program_id`.
+     * - The account data stays mutably borrowed until the guard is dropped.
+     *
+     * Returns:
+     * - `Ok(RefMut<T>)` over the stored value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` or `MappingError::Misaligned` if the stored
+     *   data layout does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     * - `ProgramError::AccountBorrowFailed` if the data is already borrowed.
+     */
+    pub fn get_mut<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<RefMut<'b, T>, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        self.value_mut_at(account, bump)
+    }
+
+    /**
+     * Mutably borrows the value of an entry account whose address and owner
+     * are checked.
+     */
+    fn value_mut_at<'b>(
+        &self,
+        account: &'b AccountInfo,
+        bump: u8,
+    ) -> Result<RefMut<'b, T>, ProgramError> {
+        let mut data = account.try_borrow_mut_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        let t_mut: &mut T = layout::value_mut(&mut data[header_len..])?;
+        if t_mut.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(RefMut::map(data, |data| {
+            bytemuck::from_bytes_mut::<T>(&mut data[header_len..])
+        }))
+    }
+
+    /**
+     * Reads the value associated with `(name, key)`, or the zero value if
+     * the entry does not exist yet, like a Solidity mapping.
+     *
+     * Nothing is created: a missing entry costs no rent and stays missing.
+     * Note the zero value also has a zero bump.
+     *
+     * Behavior:
+     * - Verifies that the passed `account` matches the derived PDA, so a
+     *   wrong account is never mistaken for a missing entry.
+     * - If the PDA is still owned by the system program, returns `T::zeroed()`.
+     * - Otherwise reads the stored value as [`Mapping::read`] does.
+     *
+     * Returns:
+     * - `Ok(T)`, the stored value or `T::zeroed()`.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the stored data length does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     */
+    pub fn get_or_default<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> Result<T, ProgramError> {
+        self.check_address(key, bump, account)?;
+        if account.owner() == &pinocchio_system::ID {
+            return Ok(bytemuck::Zeroable::zeroed());
+        }
+        self.read(key, bump, account)
+    }
+
+    /**
+     * Mutably borrows the value associated with `(name, key)`, creating the
+     * entry first if it does not exist yet.
+     *
+     * Turns read-modify-write logic into a single call:
+     * ```ignore
+     * let mut balance = balances.get_or_create(user.key(), bump, account, |bump| Balance {
+     *     bump,
+     *     ..Balance::zeroed()
+     * })?;
+     * balance.amount = balance.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+     * ```
+     *
+     * Behavior:
+     * - Verifies once that `account` is the PDA derived with `bump`.
+     * - If the PDA is still owned by the system program, calls `default`
+     *   with `bump` and creates the entry with the returned value, as
+     *   [`Mapping::create`] does. `default` must keep that bump.
+     * - Then borrows the value as [`Mapping::get_mut`] does.
+     *
+     * Returns:
+     * - `Ok(RefMut<T>)` over the stored value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `ProgramError::InvalidArgument` if the default value holds another
+     *   bump than `bump`; nothing is created.
+     * - The errors of [`Mapping::create`] and [`Mapping::get_mut`].
+     */
+    pub fn get_or_create<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+        default: impl FnOnce(u8) -> T,
+    ) -> Result<RefMut<'b, T>, ProgramError> {
+        self.check_address(key, bump, account)?;
+
+        if account.owner() == &pinocchio_system::ID {
+            let value = default(bump);
+            if value.bump() != bump {
+                return Err(ProgramError::InvalidArgument);
             }
-            .invoke_signed(&[seeds.clone()])?;
+            self.create_account(key, bump, account, self.data_len())?;
+            self.init_header(account, bump)?;
+            self.write_value(account, bump, value)?;
+        } else {
+            self.check_owner(account)?;
+        }
+        self.value_mut_at(account, bump)
+    }
 
-            let mut data = account.try_borrow_mut_data()?;
-            if data.len() != size_T {
-                return Err(ProgramError::InvalidAccountData);
+    /**
+     * Runs `f` on the value associated with `(name, key)` and stores the
+     * result, validating the entry once.
+     *
+     * `f` gets a mutable copy of the stored value, written back only if it
+     * returns `Ok`: on error the entry is left untouched. Works at any
+     * alignment, see [`Mapping::read`].
+     *
+     * ```ignore
+     * positions.modify(user.key(), bump, position_account, |position| {
+     *     position.amount = position.amount.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+     *     Ok(())
+     * })?;
+     * ```
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the modified value is stored.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA, or
+     *   `f` changed the bump.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the stored data length does not match `T`.
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     * - The error returned by `f`.
+     */
+    pub fn modify<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+        f: impl FnOnce(&mut T) -> ProgramResult,
+    ) -> ProgramResult {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        let mut value: T = layout::read_value(&data[header_len..])?;
+        if value.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        f(&mut value)?;
+        if value.bump() != bump {
+            return Err(MappingError::PdaMismatch.into());
+        }
+
+        self.count_write(&mut data)?;
+        layout::write_value(&mut data[header_len..], &value)?;
+        Ok(())
+    }
+
+    /**
+     * Compare-and-swap: overwrites the value associated with `(name, key)`
+     * with `new`, only if it still equals `expected`.
+     *
+     * Values are compared with `T`'s `PartialEq`, not byte for byte: a
+     * stored `PodBool(2)` equals `PodBool::from(true)`. The stored value is
+     * read at any alignment, see [`Mapping::read`]. The PDA is derived with
+     * the bump of `expected`, which `new` must keep.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` if the value was `expected` and is now `new`.
+     * - `MappingError::ValueMismatch` if the stored value differs from `expected`.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA, or
+     *   `new` holds another bump.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the stored data length does not match `T`.
+     */
+    pub fn update_if<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        expected: T,
+        new: T,
+        account: &AccountInfo,
+    ) -> ProgramResult
+    where
+        T: PartialEq,
+    {
+        let bump = expected.bump();
+        if new.bump() != bump {
+            return Err(MappingError::PdaMismatch.into());
+        }
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let mut data = account.try_borrow_mut_data()?;
+        self.check_data(&data, bump)?;
+
+        let header_len = self.header_len();
+        let stored: T = layout::read_value(&data[header_len..])?;
+        if stored != expected {
+            return Err(MappingError::ValueMismatch.into());
+        }
+
+        self.count_write(&mut data)?;
+        layout::write_value(&mut data[header_len..], &new)?;
+        Ok(())
+    }
+
+    /**
+     * Converts the entry associated with `(name, key)` from `Old` to `T`.
+     *
+     * Meant to upgrade entries lazily, the first time an instruction touches
+     * them after a layout change:
+     * ```ignore
+     * shares.migrate::<ShareV1>(maker.key(), bump, shares_account, |old| Share { .. })?;
+     * ```
+     *
+     * Behavior:
+     * - Verifies that the passed `account` matches the derived PDA before
+     *   touching its data.
+     * - Reads the stored value with the `Old` layout and checks the header
+     *   holds `Old`'s discriminator and version, and `bump`.
+     * - Calls `f` to build the new value, which must keep the bump.
+     * - Resizes the account to fit `T`: growing tops up the rent from
+     *   `payer`, shrinking refunds the excess to `payer`.
+     * - Writes the new value and `T`'s discriminator and schema version.
+     *
+     * Requirements:
+     * - The mapping must have a header (see [`Mapping::with_header`]):
+     *   it is what tells an old entry from a migrated one.
+     * - `Old` must implement `Pod`, `Bumpy` and `MappingValue`.
+     * - The account must already be created and owned by `program_id`.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` if the entry now holds the converted value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA, or
+     *   `f` changed the bump.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::MissingHeader` if the mapping has no header.
+     * - `MappingError::SizeMismatch` if the entry does not hold an `Old`.
+     * - `MappingError::DiscriminatorMismatch` or `MappingError::VersionMismatch`
+     *   if the header does not describe `Old` (e.g. already migrated).
+     * - `ProgramError::InvalidAccountData` if the stored bump differs from `bump`.
+     */
+    pub fn migrate<Old>(
+        self,
+        key: &(impl MappingKey + ?Sized),
+        bump: u8,
+        account: &AccountInfo,
+        f: impl FnOnce(Old) -> T,
+    ) -> ProgramResult
+    where
+        Old: Pod + Bumpy + MappingValue,
+    {
+        self.require_header()?;
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let old: Old = {
+            let data = account.try_borrow_data()?;
+            // Header first, so an entry already migrated reports it
+            let stored: &AccountHeader = data
+                .get(..HEADER_LEN)
+                .map(bytemuck::from_bytes::<AccountHeader>)
+                .ok_or(MappingError::SizeMismatch)?;
+            AccountHeader::new::<Old>(bump)
+                .with_flags(self.header.unwrap_or_default().flags)
+                .check(stored)?;
+            if data.len() != self.header_len() + core::mem::size_of::<Old>() {
+                return Err(MappingError::SizeMismatch.into());
             }
 
-            if (data.as_ptr() as usize) % core::mem::align_of::<Self>() != 0 {
-                return Err(ProgramError::InvalidAccountData);
+            layout::read_value(&data[self.header_len()..])?
+        };
+        if old.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let value = f(old);
+        if value.bump() != bump {
+            return Err(MappingError::PdaMismatch.into());
+        }
+
+        if account.data_len() != self.data_len() {
+            self.resize_account(account, self.data_len())?;
+        }
+        self.init_header(account, bump)?;
+        self.write_value(account, bump, value)
+    }
+}
+
+impl<'a, T> Mapping<'a, T> {
+    /**
+     * Offset of the value in entry data: the header and, if enabled, the
+     * write counter.
+     */
+    fn header_len(&self) -> usize {
+        header::header_len(self.header)
+    }
+
+    /**
+     * Account data length of an entry: the optional header plus `T`.
+     */
+    fn data_len(&self) -> usize {
+        self.header_len() + core::mem::size_of::<T>()
+    }
+
+    /**
+     * Writes the header of a freshly created entry, if the mapping has one.
+     */
+    fn init_header(&self, account: &AccountInfo, bump: u8) -> ProgramResult {
+        if let Some(header) = self.header {
+            let mut data = account.try_borrow_mut_data()?;
+            if data.len() != self.data_len() {
+                return Err(MappingError::SizeMismatch.into());
             }
-            let t_ref: &mut T = bytemuck::from_bytes_mut(&mut data);
-            *t_ref = value;
-            Ok(())
-        } else {
-            return Err(pinocchio::program_error::ProgramError::IllegalOwner);
+            let stored: &mut AccountHeader = bytemuck::from_bytes_mut(&mut data[..HEADER_LEN]);
+            *stored = header.with_bump(bump);
         }
+        Ok(())
+    }
+
+    /**
+     * Validates the layout of entry data: length and header (if any).
+     *
+     * The alignment of the value part is only checked where a reference to
+     * it is handed out, see [`layout::value_ref`].
+     */
+    fn check_data(&self, data: &[u8], bump: u8) -> ProgramResult {
+        header::check_entry_data(self.header, data, core::mem::size_of::<T>(), bump)?;
+        Ok(())
+    }
+
+    /**
+     * Copies `value` into the data of an existing entry account whose PDA
+     * uses `bump`. Byte copy, the data needs no alignment for `T`.
+     */
+    fn write_value(&self, account: &AccountInfo, bump: u8, value: T) -> ProgramResult
+    where
+        T: NoUninit,
+    {
+        let mut data = account.try_borrow_mut_data()?;
+        self.check_data(&data, bump)?;
+
+        self.count_write(&mut data)?;
+        let header_len = self.header_len();
+        layout::write_value(&mut data[header_len..], &value)?;
+        Ok(())
+    }
+
+    /**
+     * Increments the write counter of validated entry data, if the mapping
+     * keeps one.
+     */
+    fn count_write(&self, data: &mut [u8]) -> ProgramResult {
+        if let Some(count) = self.write_count(data) {
+            let count = count
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            data[HEADER_LEN..HEADER_LEN + WRITE_COUNTER_LEN].copy_from_slice(&count.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /**
+     * Write counter of validated entry data, `None` if the mapping keeps none.
+     */
+    fn write_count(&self, data: &[u8]) -> Option<u64> {
+        match self.header {
+            Some(header) if header.has_write_counter() => entry_version(data),
+            _ => None,
+        }
+    }
+
+    /**
+     * Fails unless the mapping stores an [`AccountHeader`] in its entries.
+     */
+    fn require_header(&self) -> ProgramResult {
+        if self.header.is_none() {
+            return Err(MappingError::MissingHeader.into());
+        }
+        Ok(())
+    }
+
+    /**
+     * Bump recorded in the header of an existing entry account.
+     */
+    fn stored_bump(&self, account: &AccountInfo) -> Result<u8, ProgramError> {
+        self.require_header()?;
+
+        let data = account.try_borrow_data()?;
+        Ok(header::stored_bump(&data)?)
+    }
+}
+
+/**
+ * Fails unless `account` is an existing entry owned by `program_id`.
+ *
+ * Accounts still held by the system program are reported as
+ * uninitialized, any other owner as a wrong owner.
+ */
+pub(crate) fn check_owner(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    if account.owner() == program_id {
+        Ok(())
+    } else if account.owner() == &pinocchio_system::ID {
+        Err(MappingError::Uninitialized.into())
+    } else {
+        Err(MappingError::WrongOwner.into())
     }
 }
 
@@ -264,13 +1073,18 @@ impl<'a> Mapping<'a> {
  * - Produces a fully initialized [`Mapping`] using:
  *     - the caller’s program ID (`crate::ID`),
  *     - the provided `name`,
- *     - the provided `payer`.
+ *     - the provided `payer`,
+ *     - the value type after `=>`, or the one inferred from the first use.
  *
  * Usage:
- * ```
- * let m = mapping!(b"my_mapping", payer_account);
+ * ```ignore
+ * let m = mapping!(b"my_mapping", payer_account => Position);
+ * m.set(&(*maker.key(), nonce), value, entry_account)?;
  * ```
  *
+ * The returned mapping accepts any [`MappingKey`] per call, so the same
+ * macro serves `Pubkey`, integer, byte-array and tuple keys.
+ *
  * Requirements:
  * - The caller crate must expose a public `ID: Pubkey` constant.
  * - `name` must be a byte-slice identifier.
@@ -278,6 +1092,12 @@ impl<'a> Mapping<'a> {
  */
 #[macro_export]
 macro_rules! mapping {
+    ($name:expr, $payer:expr => $value:ty) => {{
+        // Fail early if ID doesn't exist or is the wrong type
+        let program_id: &pinocchio::pubkey::Pubkey = &crate::ID;
+
+        $crate::Mapping::<$value>::new(program_id, $name, $payer)
+    }};
     ($name:expr, $payer:expr) => {{
         // Fail early if ID doesn't exist or is the wrong type
         let program_id: &pinocchio::pubkey::Pubkey = &crate::ID;
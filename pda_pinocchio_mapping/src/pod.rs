@@ -0,0 +1,257 @@
+/*!
+ * Alignment-1 integers for mapping value fields.
+ *
+ * `u64` and wider fields give a value struct 8- or 16-byte alignment and
+ * often padding, which rules out `Pod` and in-place views of account data.
+ * These wrappers store the little-endian bytes instead (`[u8; N]`, alignment
+ * 1) while keeping a numeric API, so value structs need neither manual
+ * `to_le_bytes`/`from_le_bytes` accessors nor padding fields.
+ */
+
+use bytemuck::{Pod, Zeroable};
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+macro_rules! pod_int {
+    ($(#[$doc:meta])* $name:ident, $int:ty) => {
+        $(#[$doc])*
+        #[repr(transparent)]
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+        pub struct $name(pub [u8; core::mem::size_of::<$int>()]);
+
+        impl $name {
+            pub const ZERO: Self = Self::new(0);
+
+            pub const fn new(value: $int) -> Self {
+                Self(value.to_le_bytes())
+            }
+
+            pub const fn get(self) -> $int {
+                <$int>::from_le_bytes(self.0)
+            }
+
+            pub fn set(&mut self, value: $int) {
+                *self = Self::new(value);
+            }
+
+            pub fn checked_add(self, rhs: $int) -> Option<Self> {
+                self.get().checked_add(rhs).map(Self::new)
+            }
+
+            pub fn checked_sub(self, rhs: $int) -> Option<Self> {
+                self.get().checked_sub(rhs).map(Self::new)
+            }
+
+            pub fn checked_mul(self, rhs: $int) -> Option<Self> {
+                self.get().checked_mul(rhs).map(Self::new)
+            }
+
+            pub fn checked_div(self, rhs: $int) -> Option<Self> {
+                self.get().checked_div(rhs).map(Self::new)
+            }
+
+            pub fn saturating_add(self, rhs: $int) -> Self {
+                Self::new(self.get().saturating_add(rhs))
+            }
+
+            pub fn saturating_sub(self, rhs: $int) -> Self {
+                Self::new(self.get().saturating_sub(rhs))
+            }
+        }
+
+        impl From<$int> for $name {
+            fn from(value: $int) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for $int {
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        // Numeric order, the derived one would compare the LE bytes
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.get().cmp(&other.get())
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.get(), f)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.get(), f)
+            }
+        }
+    };
+}
+
+pod_int!(
+    /// Little-endian `u16` with alignment 1.
+    PodU16,
+    u16
+);
+pod_int!(
+    /// Little-endian `u32` with alignment 1.
+    PodU32,
+    u32
+);
+pod_int!(
+    /// Little-endian `u64` with alignment 1.
+    PodU64,
+    u64
+);
+pod_int!(
+    /// Little-endian `i64` with alignment 1.
+    PodI64,
+    i64
+);
+pod_int!(
+    /// Little-endian `u128` with alignment 1.
+    PodU128,
+    u128
+);
+
+/**
+ * `bool` stored as a byte, `Pod` unlike `bool` itself.
+ *
+ * Any non-zero byte reads as `true`, so no stored value is invalid.
+ * Equality and hashing follow [`PodBool::get`]: `PodBool(2) == PodBool::TRUE`.
+ */
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+pub struct PodBool(pub u8);
+
+impl PodBool {
+    pub const FALSE: Self = Self(0);
+    pub const TRUE: Self = Self(1);
+
+    pub const fn new(value: bool) -> Self {
+        Self(value as u8)
+    }
+
+    pub const fn get(self) -> bool {
+        self.0 != 0
+    }
+
+    pub fn set(&mut self, value: bool) {
+        *self = Self::new(value);
+    }
+}
+
+impl From<bool> for PodBool {
+    fn from(value: bool) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<PodBool> for bool {
+    fn from(value: PodBool) -> Self {
+        value.get()
+    }
+}
+
+impl PartialEq for PodBool {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl Eq for PodBool {}
+
+impl Hash for PodBool {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.get().hash(state);
+    }
+}
+
+impl fmt::Debug for PodBool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Pod, Zeroable)]
+    struct Position {
+        amount: PodU64,
+        debt: PodU128,
+        pnl: PodI64,
+        active: PodBool,
+        bump: u8,
+    }
+
+    #[test]
+    fn wrappers_have_alignment_one_and_no_padding() {
+        assert_eq!(core::mem::align_of::<PodU128>(), 1);
+        assert_eq!(core::mem::align_of::<Position>(), 1);
+        assert_eq!(core::mem::size_of::<Position>(), 8 + 16 + 8 + 1 + 1);
+    }
+
+    #[test]
+    fn values_are_stored_little_endian() {
+        assert_eq!(PodU32::new(0x0102_0304).0, [4, 3, 2, 1]);
+        assert_eq!(PodI64::new(-1).0, [0xff; 8]);
+        assert_eq!(u64::from(PodU64::from(42)), 42);
+        assert!(bool::from(PodBool::from(true)));
+        assert!(PodBool(7).get());
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let amount = PodU64::new(10);
+        assert_eq!(amount.checked_add(5), Some(PodU64::new(15)));
+        assert_eq!(amount.checked_sub(11), None);
+        assert_eq!(PodU64::new(u64::MAX).checked_add(1), None);
+        assert_eq!(PodU16::new(300).checked_mul(300), None);
+        assert_eq!(amount.checked_div(0), None);
+        assert_eq!(
+            PodI64::new(-5).saturating_sub(i64::MAX),
+            PodI64::new(i64::MIN)
+        );
+    }
+
+    #[test]
+    fn any_non_zero_byte_equals_true() {
+        use std::hash::DefaultHasher;
+
+        let hash = |value: PodBool| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        assert_eq!(PodBool(7), PodBool::TRUE);
+        assert_eq!(hash(PodBool(7)), hash(PodBool::TRUE));
+        assert_ne!(PodBool(7), PodBool::FALSE);
+        assert_eq!(PodBool::default(), PodBool::FALSE);
+    }
+
+    #[test]
+    fn ordering_is_numeric() {
+        // 256 = [0, 1], 1 = [1, 0]: byte order would say 256 < 1
+        assert!(PodU16::new(256) > PodU16::new(1));
+        assert!(PodI64::new(-1) < PodI64::new(0));
+    }
+}
@@ -0,0 +1,347 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{Bumpy, Mapping, MappingError, MappingKey};
+
+/**
+ * Value stored Borsh-serialized by the `*_serialized` methods.
+ *
+ * Usage:
+ * ```ignore
+ * #[derive(BorshSerialize, BorshDeserialize)]
+ * pub struct Profile {
+ *     pub nickname: String,
+ *     pub referrer: Option<Pubkey>,
+ *     pub bump: u8,
+ * }
+ *
+ * impl BorshValue for Profile {
+ *     const MAX_LEN: Option<usize> = Some(4 + 32 + 1 + 32 + 1);
+ * }
+ * ```
+ */
+pub trait BorshValue: BorshSerialize + BorshDeserialize + Bumpy {
+    /// Fixed account data length. `None` sizes the account to the
+    /// serialized value, resizing it whenever that length changes.
+    const MAX_LEN: Option<usize> = None;
+}
+
+/**
+ * Borsh-serialized entries, for values that cannot be `Pod` (`Option`,
+ * `String`, `Vec`, ...). Requires the `borsh` feature.
+ *
+ * Entries use the same PDA derivation, creation and ownership checks as
+ * [`Mapping::set`], but are (de)serialized instead of viewed in place, so
+ * reads return owned values. Entries carry no [`AccountHeader`](crate::AccountHeader).
+ *
+ * Account size:
+ * - with `T::MAX_LEN`, always `MAX_LEN` bytes, the value followed by zeroes;
+ * - without, exactly the serialized length: writes of another length resize
+ *   the account, topping up or refunding rent with `payer`.
+ */
+impl<'a, T: BorshValue> Mapping<'a, T> {
+    /**
+     * Creates or overwrites the entry associated with `(name, key)` with
+     * the serialized `value`, see [`Mapping::set`].
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if `value` serializes to more than `T::MAX_LEN`.
+     * - `ProgramError` if system-instruction or resize failures occur.
+     */
+    pub fn set_serialized<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: &T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        let space = Self::serialized_space(value)?;
+        self.check_address(key, value.bump(), account)?;
+
+        if account.owner() != self.program_id {
+            if account.owner() != &pinocchio_system::ID {
+                return Err(MappingError::WrongOwner.into());
+            }
+            self.create_account(key, value.bump(), account, space)?;
+        } else if account.data_len() != space {
+            self.resize_account(account, space)?;
+        }
+
+        Self::write_serialized(account, value)
+    }
+
+    /**
+     * Overwrites the existing entry associated with `(name, key)` with the
+     * serialized `value`, see [`Mapping::update`].
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if `value` serializes to more than `T::MAX_LEN`.
+     */
+    pub fn update_serialized<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: &T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        let space = Self::serialized_space(value)?;
+        self.check_address(key, value.bump(), account)?;
+        self.check_owner(account)?;
+
+        if account.data_len() != space {
+            self.resize_account(account, space)?;
+        }
+
+        Self::write_serialized(account, value)
+    }
+
+    /**
+     * Creates the entry associated with `(name, key)` for the first time,
+     * see [`Mapping::create`].
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::AlreadyInitialized` if the PDA already exists and is owned.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if `value` serializes to more than `T::MAX_LEN`.
+     */
+    pub fn create_serialized<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: &T,
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        let space = Self::serialized_space(value)?;
+        self.check_address(key, value.bump(), account)?;
+
+        if account.owner() == self.program_id {
+            return Err(MappingError::AlreadyInitialized.into());
+        }
+        if account.owner() != &pinocchio_system::ID {
+            return Err(MappingError::WrongOwner.into());
+        }
+
+        self.create_account(key, value.bump(), account, space)?;
+        Self::write_serialized(account, value)
+    }
+
+    /**
+     * Deserializes the value stored in the entry associated with `(name, key)`.
+     *
+     * Returns:
+     * - `Ok(T)`, the deserialized value.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `ProgramError::InvalidAccountData` if the data is no serialized `T`
+     *   (or, without `T::MAX_LEN`, has trailing bytes), or the stored bump
+     *   differs from `bump`.
+     */
+    pub fn get_deserialized<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> Result<T, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let value = Self::decode(&account.try_borrow_data()?)?;
+        if value.bump() != bump {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(value)
+    }
+
+    /**
+     * Account data length of an entry holding `value`.
+     */
+    fn serialized_space(value: &T) -> Result<usize, ProgramError> {
+        let len = borsh::object_length(value).map_err(|_| ProgramError::BorshIoError)?;
+        match T::MAX_LEN {
+            Some(max_len) if len > max_len => Err(MappingError::SizeMismatch.into()),
+            Some(max_len) => Ok(max_len),
+            None => Ok(len),
+        }
+    }
+
+    /**
+     * Serializes `value` into the data of an entry account.
+     */
+    fn write_serialized(account: &AccountInfo, value: &T) -> ProgramResult {
+        Self::encode(value, &mut account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /**
+     * Serializes `value` at the start of `data` and zeroes the rest.
+     */
+    fn encode(value: &T, data: &mut [u8]) -> Result<(), MappingError> {
+        let mut bytes: &mut [u8] = data;
+        value
+            .serialize(&mut bytes)
+            .map_err(|_| MappingError::SizeMismatch)?;
+        bytes.fill(0);
+        Ok(())
+    }
+
+    /**
+     * Deserializes the value of entry data. Without `T::MAX_LEN`, the data
+     * must hold nothing else.
+     */
+    fn decode(data: &[u8]) -> Result<T, ProgramError> {
+        let mut bytes: &[u8] = data;
+        let value = T::deserialize(&mut bytes).map_err(|_| ProgramError::InvalidAccountData)?;
+        if T::MAX_LEN.is_none() && !bytes.is_empty() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::io::{Read, Result, Write};
+
+    /// Variable-size value: 2 or 34 bytes serialized.
+    #[derive(Debug, PartialEq)]
+    struct Profile {
+        referrer: Option<[u8; 32]>,
+        bump: u8,
+    }
+
+    /// `Profile` stored in accounts of `MAX` bytes.
+    #[derive(Debug, PartialEq)]
+    struct Fixed<const MAX: usize>(Profile);
+
+    impl BorshSerialize for Profile {
+        fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            self.referrer.serialize(writer)?;
+            self.bump.serialize(writer)
+        }
+    }
+
+    impl BorshDeserialize for Profile {
+        fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+            Ok(Self {
+                referrer: BorshDeserialize::deserialize_reader(reader)?,
+                bump: BorshDeserialize::deserialize_reader(reader)?,
+            })
+        }
+    }
+
+    impl<const MAX: usize> BorshSerialize for Fixed<MAX> {
+        fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            self.0.serialize(writer)
+        }
+    }
+
+    impl<const MAX: usize> BorshDeserialize for Fixed<MAX> {
+        fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+            Ok(Self(Profile::deserialize_reader(reader)?))
+        }
+    }
+
+    impl Bumpy for Profile {
+        fn bump(&self) -> u8 {
+            self.bump
+        }
+    }
+
+    impl<const MAX: usize> Bumpy for Fixed<MAX> {
+        fn bump(&self) -> u8 {
+            self.0.bump
+        }
+    }
+
+    impl BorshValue for Profile {}
+
+    impl<const MAX: usize> BorshValue for Fixed<MAX> {
+        const MAX_LEN: Option<usize> = Some(MAX);
+    }
+
+    type Profiles<'a> = Mapping<'a, Profile>;
+    type PaddedProfiles<'a> = Mapping<'a, Fixed<34>>;
+    /// Only fits profiles without referrer.
+    type TightProfiles<'a> = Mapping<'a, Fixed<2>>;
+
+    const ANONYMOUS: Profile = Profile {
+        referrer: None,
+        bump: 254,
+    };
+
+    const REFERRED: Profile = Profile {
+        referrer: Some([7; 32]),
+        bump: 254,
+    };
+
+    #[test]
+    fn max_len_pads_the_value_with_zeroes() {
+        let value = Fixed::<34>(ANONYMOUS);
+        assert_eq!(PaddedProfiles::serialized_space(&value), Ok(34));
+
+        let mut data = [0xff; 34];
+        PaddedProfiles::encode(&value, &mut data).unwrap();
+        assert_eq!(data[..2], [0, 254]);
+        assert!(data[2..].iter().all(|&byte| byte == 0));
+
+        assert_eq!(PaddedProfiles::decode(&data), Ok(value));
+    }
+
+    #[test]
+    fn values_longer_than_max_len_are_rejected() {
+        let value = Fixed::<2>(REFERRED);
+        assert_eq!(
+            TightProfiles::serialized_space(&value),
+            Err(MappingError::SizeMismatch.into())
+        );
+        assert_eq!(
+            TightProfiles::encode(&value, &mut [0; 2]),
+            Err(MappingError::SizeMismatch)
+        );
+
+        assert_eq!(TightProfiles::serialized_space(&Fixed(ANONYMOUS)), Ok(2));
+    }
+
+    #[test]
+    fn unpadded_values_use_their_exact_length() {
+        assert_eq!(Profiles::serialized_space(&REFERRED), Ok(34));
+        assert_eq!(Profiles::serialized_space(&ANONYMOUS), Ok(2));
+
+        let mut data = [0; 34];
+        Profiles::encode(&REFERRED, &mut data).unwrap();
+        assert_eq!(Profiles::decode(&data), Ok(REFERRED));
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected_without_max_len() {
+        // `ANONYMOUS` followed by zeroes, as left by a longer value
+        let mut data = [0xff; 34];
+        Profiles::encode(&ANONYMOUS, &mut data).unwrap();
+
+        assert_eq!(
+            Profiles::decode(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(Profiles::decode(&data[..2]), Ok(ANONYMOUS));
+
+        // Padding is expected with `MAX_LEN`
+        assert_eq!(PaddedProfiles::decode(&data), Ok(Fixed(ANONYMOUS)));
+    }
+
+    #[test]
+    fn truncated_data_is_rejected() {
+        assert_eq!(
+            Profiles::decode(&[1, 7, 7]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(Profiles::decode(&[]), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -0,0 +1,192 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    cpi::invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::{key::PdaSeeds, Mapping, MappingError, MappingKey};
+
+/// SPL Token program.
+const TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+/// SPL Token-2022 program.
+const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/**
+ * Accounts and amount of a token transfer out of an account whose authority
+ * is a mapping entry, see [`Mapping::transfer_tokens`].
+ *
+ * Sent as `TransferChecked`, so it works with SPL Token and Token-2022 mints.
+ */
+pub struct TokenTransfer<'b> {
+    /// SPL Token or Token-2022 program.
+    pub token_program: &'b AccountInfo,
+    /// Token account to debit, its authority being the entry PDA.
+    pub from: &'b AccountInfo,
+    pub mint: &'b AccountInfo,
+    /// Token account to credit.
+    pub to: &'b AccountInfo,
+    /// The entry PDA.
+    pub authority: &'b AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/**
+ * Entries as CPI signers: mapping PDAs can own lamports and token accounts
+ * and sign for them with `[name, key seeds.., bump]`.
+ *
+ * Usage:
+ * ```ignore
+ * let vaults = mapping!(b"vaults", payer => Vault);
+ * vaults.with_signer(user.key(), bump, |signer| {
+ *     CloseAccount { account, destination, authority }.invoke_signed(&[signer])
+ * })?;
+ * ```
+ */
+impl<'a, T: ?Sized> Mapping<'a, T> {
+    /**
+     * Calls `f` with the `Signer` of the entry associated with `(name, key)`,
+     * ready for `invoke_signed`.
+     *
+     * The seeds live on this call's stack, hence the closure. The entry
+     * account is not checked: a `bump` that does not derive a valid PDA only
+     * makes the CPI fail.
+     *
+     * Returns the result of `f`, or `ProgramError::InvalidSeeds` without
+     * calling it if the key has more than [`MAX_KEY_SEEDS`](crate::MAX_KEY_SEEDS) seeds.
+     */
+    pub fn with_signer<K: MappingKey + ?Sized, R>(
+        &self,
+        key: &K,
+        bump: u8,
+        f: impl FnOnce(Signer) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError> {
+        let bump = [bump];
+        let key_seeds = key.seeds();
+        let seeds = PdaSeeds::new(self.name, &key_seeds, &bump)?;
+        let signer_seeds = seeds.signer_seeds();
+
+        f(Signer::from(&signer_seeds[..seeds.len()]))
+    }
+
+    /**
+     * Moves `amount` lamports out of the entry account associated with
+     * `(name, key)` into `to`.
+     *
+     * Behavior:
+     * - An entry owned by `program_id` is debited directly, and must keep
+     *   the rent-exempt minimum of its data.
+     * - An entry still owned by the system program (a plain SOL holder) is
+     *   debited with a system `Transfer` signed by the PDA.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the lamports are moved.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `ProgramError::InsufficientFunds` if the entry would drop below its
+     *   rent-exempt minimum.
+     * - `ProgramError::ArithmeticOverflow` if `to` lamports would overflow.
+     */
+    pub fn transfer_lamports<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+        to: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        self.check_address(key, bump, account)?;
+
+        if account.owner() == &pinocchio_system::ID {
+            return self.with_signer(key, bump, |signer| {
+                Transfer {
+                    from: account,
+                    to,
+                    lamports: amount,
+                }
+                .invoke_signed(&[signer])
+            });
+        }
+        if account.owner() != self.program_id {
+            return Err(MappingError::WrongOwner.into());
+        }
+
+        let remaining = account
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        if remaining < self.minimum_balance(account.data_len())? {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        *account.try_borrow_mut_lamports()? = remaining;
+        let mut to_lamports = to.try_borrow_mut_lamports()?;
+        *to_lamports = to_lamports
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /**
+     * Moves tokens out of a token account whose authority is the entry
+     * associated with `(name, key)`, signing as the entry PDA.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the tokens are moved.
+     * - `MappingError::PdaMismatch` if `transfer.authority` is not the derived PDA.
+     * - `ProgramError::IncorrectProgramId` if `transfer.token_program` is
+     *   neither SPL Token nor Token-2022, so the PDA never signs for
+     *   another program.
+     * - Errors of the token program, e.g. insufficient balance.
+     */
+    pub fn transfer_tokens<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        transfer: TokenTransfer,
+    ) -> ProgramResult {
+        self.check_address(key, bump, transfer.authority)?;
+
+        let token_program = transfer.token_program.key();
+        if token_program != &TOKEN_PROGRAM_ID && token_program != &TOKEN_2022_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // TransferChecked: [12, amount (u64 LE), decimals]
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&transfer.amount.to_le_bytes());
+        data[9] = transfer.decimals;
+
+        let accounts = [
+            AccountMeta::new(transfer.from.key(), true, false),
+            AccountMeta::new(transfer.mint.key(), false, false),
+            AccountMeta::new(transfer.to.key(), true, false),
+            AccountMeta::new(transfer.authority.key(), false, true),
+        ];
+        let instruction = Instruction {
+            program_id: token_program,
+            data: &data,
+            accounts: &accounts,
+        };
+
+        self.with_signer(key, bump, |signer| {
+            invoke_signed(
+                &instruction,
+                &[
+                    transfer.from,
+                    transfer.mint,
+                    transfer.to,
+                    transfer.authority,
+                ],
+                &[signer],
+            )
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use bytemuck::Pod;
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    program_error::ProgramError,
+    ProgramResult,
+};
+
+use crate::{Mapping, MappingError, MappingKey};
+
+/**
+ * Variable-size entries: a `Mapping<'a, [E]>` stores a slice of `E` per key,
+ * and `Mapping<'a, [u8]>` raw bytes (strings, serialized data, ...).
+ *
+ * The account is resized in place whenever a write changes the slice length.
+ * Values carry no bump, so it is passed explicitly to every call.
+ *
+ * Usage:
+ * ```ignore
+ * let lists = mapping!(b"lists", payer => [Item]);
+ * lists.set_slice(user.key(), bump, &items, list_account)?;
+ * ```
+ */
+impl<'a, E: Pod> Mapping<'a, [E]> {
+    /**
+     * Writes `values` into the PDA account associated with `(name, key)`.
+     *
+     * This method derives the PDA using:
+     *   - the mapping's static `name`,
+     *   - the seeds of the provided `key` (see [`MappingKey`]),
+     *   - the provided `bump`.
+     *
+     * Behavior:
+     * - If the PDA account does not exist, it is created with room for
+     *   `values` and a rent-exempt balance.
+     * - If it exists with another length, it is resized first: growing tops
+     *   up the rent from `payer`, shrinking refunds the excess to `payer`.
+     * - The account data is then overwritten with `values`.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `ProgramError` if system-instruction or resize failures occur.
+     */
+    pub fn set_slice<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        values: &[E],
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        let len = core::mem::size_of_val(values);
+        self.check_address(key, bump, account)?;
+
+        if account.owner() != self.program_id {
+            if account.owner() != &pinocchio_system::ID {
+                return Err(MappingError::WrongOwner.into());
+            }
+            self.create_account(key, bump, account, len)?;
+        } else if account.data_len() != len {
+            self.resize_account(account, len)?;
+        }
+
+        Self::write_slice(account, values)
+    }
+
+    /**
+     * Overwrites the slice stored in an existing entry, resizing the account
+     * to the length of `values` as [`Mapping::set_slice`] does.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     */
+    pub fn update_slice<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        values: &[E],
+        account: &AccountInfo,
+    ) -> ProgramResult {
+        let len = core::mem::size_of_val(values);
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        if account.data_len() != len {
+            self.resize_account(account, len)?;
+        }
+
+        Self::write_slice(account, values)
+    }
+
+    /**
+     * Borrows the slice stored in the PDA account associated with `(name, key)`.
+     *
+     * Like [`Mapping::get`], returns a `Ref` guard into the account data,
+     * which stays borrowed until the guard is dropped.
+     *
+     * Returns:
+     * - `Ok(Ref<[E]>)` over the stored elements.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the data is not a whole number of `E`,
+     *   or `E` is zero-sized.
+     * - `MappingError::Misaligned` if the data is not aligned for `E`.
+     */
+    pub fn get_slice<'b, K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &'b AccountInfo,
+    ) -> Result<Ref<'b, [E]>, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        let data = account.try_borrow_data()?;
+        if data.len().checked_rem(core::mem::size_of::<E>()) != Some(0) {
+            return Err(MappingError::SizeMismatch.into());
+        }
+        bytemuck::try_cast_slice::<u8, E>(&data).map_err(|_| MappingError::Misaligned)?;
+
+        Ok(Ref::map(data, |data| bytemuck::cast_slice::<u8, E>(data)))
+    }
+
+    fn write_slice(account: &AccountInfo, values: &[E]) -> ProgramResult {
+        let mut data = account.try_borrow_mut_data()?;
+        if data.len() != core::mem::size_of_val(values) {
+            return Err(MappingError::SizeMismatch.into());
+        }
+
+        // Byte copy, the account data needs no alignment for `E` here
+        data.copy_from_slice(bytemuck::cast_slice(values));
+        Ok(())
+    }
+}
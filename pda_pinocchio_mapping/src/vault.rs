@@ -0,0 +1,186 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, sysvars::rent::Rent,
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer;
+
+use crate::{Mapping, MappingError, MappingKey};
+
+/**
+ * Per-key SOL balances, each held by its own program-owned PDA.
+ *
+ * A vault entry is an empty account derived from `[name, key seeds.., bump]`
+ * like any [`Mapping`] entry; its balance is whatever it holds above the
+ * rent-exempt minimum. Deposits go through a system transfer, withdrawals
+ * debit the vault directly since the program owns it.
+ *
+ * Usage:
+ * ```ignore
+ * let vaults = VaultMapping::new(&crate::ID, b"vaults", payer);
+ * vaults.deposit(user.key(), bump, vault_account, user, amount)?;
+ * vaults.withdraw(user.key(), bump, vault_account, user, amount)?;
+ * ```
+ *
+ * Authorization is left to the caller: check who may withdraw from which
+ * key before calling [`VaultMapping::withdraw`].
+ */
+#[derive(Clone, Copy)]
+pub struct VaultMapping<'a> {
+    mapping: Mapping<'a, [u8]>,
+}
+
+impl<'a> VaultMapping<'a> {
+    pub fn new(program_id: &'a Pubkey, name: &'static [u8], payer: &'a AccountInfo) -> Self {
+        Self {
+            mapping: Mapping::new(program_id, name, payer),
+        }
+    }
+
+    /**
+     * Only accepts canonical bumps when creating vaults, see [`Mapping::strict`].
+     */
+    pub fn strict(mut self) -> Self {
+        self.mapping = self.mapping.strict();
+        self
+    }
+
+    /**
+     * Uses `rent` instead of the Rent sysvar, see [`Mapping::with_rent`].
+     */
+    pub fn with_rent(mut self, rent: &'a Rent) -> Self {
+        self.mapping = self.mapping.with_rent(rent);
+        self
+    }
+
+    /**
+     * Moves `amount` lamports from `from` into the vault of `key`.
+     *
+     * Behavior:
+     * - If the vault does not exist, it is created first, `payer` covering
+     *   its rent-exempt minimum (lamports already sent to the address count
+     *   towards it).
+     * - `amount` is then moved with a system `Transfer`, so `from` must sign.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the lamports are deposited.
+     * - `MappingError::PdaMismatch` if `vault` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the PDA holds data, i.e. is no vault.
+     * - `ProgramError` if system-instruction failures occur.
+     */
+    pub fn deposit<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        vault: &AccountInfo,
+        from: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        self.mapping.check_address(key, bump, vault)?;
+
+        if vault.owner() == &pinocchio_system::ID {
+            self.mapping.create_account(key, bump, vault, 0)?;
+        } else {
+            self.check_vault(vault)?;
+        }
+
+        Transfer {
+            from,
+            to: vault,
+            lamports: amount,
+        }
+        .invoke()
+    }
+
+    /**
+     * Moves `amount` lamports from the vault of `key` into `to`.
+     *
+     * The vault keeps at least its rent-exempt minimum, see
+     * [`Mapping::transfer_lamports`]; use [`VaultMapping::close`] to empty it.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the lamports are withdrawn.
+     * - `MappingError::PdaMismatch` if `vault` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the vault does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the PDA holds data, i.e. is no vault.
+     * - `ProgramError::InsufficientFunds` if `amount` exceeds the balance.
+     */
+    pub fn withdraw<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        vault: &AccountInfo,
+        to: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        self.mapping.check_address(key, bump, vault)?;
+        self.check_vault(vault)?;
+
+        self.mapping.transfer_lamports(key, bump, vault, to, amount)
+    }
+
+    /**
+     * Returns the withdrawable balance of the vault of `key`: its lamports
+     * above the rent-exempt minimum. A vault that does not exist yet has a
+     * zero balance.
+     *
+     * Returns:
+     * - `Ok(balance)` in lamports.
+     * - `MappingError::PdaMismatch` if `vault` is not the derived PDA.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the PDA holds data, i.e. is no vault.
+     */
+    pub fn balance<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        vault: &AccountInfo,
+    ) -> Result<u64, ProgramError> {
+        self.mapping.check_address(key, bump, vault)?;
+        if vault.owner() == &pinocchio_system::ID {
+            return Ok(0);
+        }
+        self.check_vault(vault)?;
+
+        Ok(vault
+            .lamports()
+            .saturating_sub(self.mapping.minimum_balance(0)?))
+    }
+
+    /**
+     * Closes the vault of `key`, moving all of its lamports, rent included,
+     * into `recipient`. See [`Mapping::remove`].
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` once the vault is closed.
+     * - `MappingError::PdaMismatch` if `vault` is not the derived PDA,
+     *   checked before anything else is read.
+     * - `MappingError::Uninitialized` if the vault does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::SizeMismatch` if the PDA holds data, i.e. is no vault.
+     */
+    pub fn close<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        vault: &AccountInfo,
+        recipient: &AccountInfo,
+    ) -> ProgramResult {
+        self.mapping.check_address(key, bump, vault)?;
+        self.check_vault(vault)?;
+
+        self.mapping.close_account(vault, recipient)
+    }
+
+    /**
+     * Fails unless `vault` is an existing, data-less entry of the program.
+     */
+    fn check_vault(&self, vault: &AccountInfo) -> ProgramResult {
+        self.mapping.check_owner(vault)?;
+        if vault.data_len() != 0 {
+            return Err(MappingError::SizeMismatch.into());
+        }
+        Ok(())
+    }
+}
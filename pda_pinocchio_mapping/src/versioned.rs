@@ -0,0 +1,113 @@
+use bytemuck::Pod;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{AccountHeader, Bumpy, Mapping, MappingError, MappingKey};
+
+/**
+ * Optimistic concurrency: a per-entry write counter.
+ *
+ * With [`Mapping::with_write_counter`] every entry keeps a `u64` counter
+ * right after its [`AccountHeader`], incremented by each write of the
+ * value (`set`, `update`, `create`, `modify`, `update_if`, `migrate`, the
+ * `*_entry` and `*_checked` writes). A client reads the counter with
+ * [`entry_version`](crate::entry_version), and the program applies its
+ * update with [`Mapping::update_versioned`] only if nobody wrote the entry
+ * in between.
+ *
+ * In-place edits through `get_mut` / `get_entry_mut` do not go through a
+ * write and leave the counter unchanged.
+ *
+ * Usage:
+ * ```ignore
+ * let orders = mapping!(b"orders", payer => Order).with_write_counter();
+ * orders.update_versioned(maker.key(), order, order_account, expected_version)?;
+ * ```
+ */
+impl<'a, T: Pod> Mapping<'a, T> {
+    /**
+     * Stores a write counter after the header of every entry of this
+     * mapping, adding a bump-only header (see [`Mapping::with_bump_header`])
+     * if the mapping has none.
+     *
+     * Entries are `WRITE_COUNTER_LEN` bytes larger, and their header has the
+     * [`AccountHeader::WRITE_COUNTER`] flag set. Existing entries without
+     * counter fail validation with `MappingError::SizeMismatch`, or
+     * `MappingError::FlagsMismatch` if their length happens to match.
+     */
+    pub fn with_write_counter(mut self) -> Self {
+        let header = self.header.unwrap_or_default();
+        self.header = Some(header.with_flags(header.flags | AccountHeader::WRITE_COUNTER));
+        self
+    }
+}
+
+impl<'a, T: Pod + Bumpy> Mapping<'a, T> {
+    /**
+     * Overwrites the existing entry associated with `(name, key)` with
+     * `value`, provided its write counter still equals `expected_version`.
+     *
+     * Behavior:
+     * - Same checks as [`Mapping::update`].
+     * - Compares the stored write counter with `expected_version`, then
+     *   writes `value` and increments the counter.
+     *
+     * Returns:
+     * - `ProgramResult::Ok(())` on success.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::MissingHeader` if the mapping has no write counter.
+     * - `MappingError::StaleVersion` if the entry was written since
+     *   `expected_version` was read.
+     * - `MappingError::SizeMismatch` / header errors if the data is not a `T` entry.
+     */
+    pub fn update_versioned<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        value: T,
+        account: &AccountInfo,
+        expected_version: u64,
+    ) -> ProgramResult {
+        self.check_address(key, value.bump(), account)?;
+        self.check_owner(account)?;
+
+        if self.get_version_at(account, value.bump())? != expected_version {
+            return Err(MappingError::StaleVersion.into());
+        }
+        self.write_value(account, value.bump(), value)
+    }
+
+    /**
+     * Returns the write counter of the existing entry associated with
+     * `(name, key)`, the on-chain counterpart of [`entry_version`](crate::entry_version).
+     *
+     * Returns:
+     * - `Ok(u64)`, the number of writes of the entry.
+     * - `MappingError::PdaMismatch` if `account` is not the derived PDA.
+     * - `MappingError::Uninitialized` if the PDA does not exist yet.
+     * - `MappingError::WrongOwner` if the PDA is owned by another program.
+     * - `MappingError::MissingHeader` if the mapping has no write counter.
+     */
+    pub fn get_version<K: MappingKey + ?Sized>(
+        self,
+        key: &K,
+        bump: u8,
+        account: &AccountInfo,
+    ) -> Result<u64, ProgramError> {
+        self.check_address(key, bump, account)?;
+        self.check_owner(account)?;
+
+        self.get_version_at(account, bump)
+    }
+
+    /**
+     * Write counter of an entry account whose address and owner are checked.
+     */
+    fn get_version_at(&self, account: &AccountInfo, bump: u8) -> Result<u64, ProgramError> {
+        let data = account.try_borrow_data()?;
+        self.check_data(&data, bump)?;
+
+        self.write_count(&data)
+            .ok_or_else(|| MappingError::MissingHeader.into())
+    }
+}
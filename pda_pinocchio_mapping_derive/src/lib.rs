@@ -0,0 +1,225 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Index, ItemStruct, LitByteStr,
+    LitInt, LitStr, Member,
+};
+
+/**
+ * Implements `pda_pinocchio_mapping::Bumpy` for a struct.
+ *
+ * The bump is read from the field marked `#[bump]`, or else from the field
+ * named `bump`. That field must be a `u8`.
+ *
+ * Usage:
+ * ```ignore
+ * #[derive(Bumpy)]
+ * pub struct Share {
+ *     pub amount: [u8; 8],
+ *     pub bump: u8,
+ * }
+ * ```
+ */
+#[proc_macro_derive(Bumpy, attributes(bump))]
+pub fn derive_bumpy(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    bumpy_impl(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn bumpy_impl(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`#[derive(Bumpy)]` only supports structs",
+        ));
+    };
+
+    let tagged = data
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.attrs.iter().any(|attr| attr.path().is_ident("bump")))
+        .collect::<Vec<_>>();
+    if tagged.len() > 1 {
+        return Err(syn::Error::new(
+            tagged[1].1.span(),
+            "only one field may be marked `#[bump]`",
+        ));
+    }
+
+    let field = tagged.into_iter().next().or_else(|| {
+        data.fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.ident.as_ref().is_some_and(|ident| ident == "bump"))
+    });
+    let Some((index, field)) = field else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`#[derive(Bumpy)]` needs a `bump: u8` field or a field marked `#[bump]`",
+        ));
+    };
+
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(index)),
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Points a wrong field type at the field itself
+    let bump = quote_spanned! {field.ty.span()=> {
+        let bump: u8 = self.#member;
+        bump
+    }};
+
+    Ok(quote! {
+        impl #impl_generics ::pda_pinocchio_mapping::Bumpy for #name #ty_generics #where_clause {
+            fn bump(&self) -> u8 #bump
+        }
+    })
+}
+
+/**
+ * Turns a struct into a mapping value type.
+ *
+ * Adds to the struct:
+ * - `#[repr(C)]` and `#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]`,
+ * - an `impl MappingValue` whose discriminator is derived from the type name,
+ * - `MAPPING_NAME` and a `mapping(program_id, payer)` constructor returning
+ *   the `Mapping` bound to `name`, with a header (`Mapping::with_header`)
+ *   so entries carry the discriminator and version,
+ * - compile-time checks that every field is `Pod` and that the struct has no
+ *   padding, reported on the offending field or struct.
+ *
+ * Arguments:
+ * - `name = "..."` (required): the mapping name used as first PDA seed.
+ * - `version = N` (optional, default 0): the schema version in the header.
+ *
+ * Usage:
+ * ```ignore
+ * #[mapping_value(name = "shares", version = 1)]
+ * #[derive(Bumpy, Debug)]
+ * pub struct Share {
+ *     pub amount: [u8; 8],
+ *     pub bump: u8,
+ * }
+ *
+ * let shares = Share::mapping(&crate::ID, payer);
+ * ```
+ *
+ * Requirements:
+ * - The calling crate must depend on `bytemuck` (with `derive`) and `pinocchio`.
+ * - The struct must not be generic and must not already derive `Clone`,
+ *   `Copy`, `Pod` or `Zeroable`.
+ */
+#[proc_macro_attribute]
+pub fn mapping_value(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+    let mut version: Option<LitInt> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("version") {
+            version = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"...\"` or `version = N`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+    let item = parse_macro_input!(input as ItemStruct);
+
+    mapping_value_impl(name, version, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn mapping_value_impl(
+    name: Option<LitStr>,
+    version: Option<LitInt>,
+    item: ItemStruct,
+) -> syn::Result<TokenStream2> {
+    let Some(name) = name else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "`#[mapping_value]` needs the mapping name: `#[mapping_value(name = \"...\")]`",
+        ));
+    };
+    let version = match version {
+        Some(version) => version.base10_parse::<u8>()?,
+        None => 0,
+    };
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            item.generics.span(),
+            "`#[mapping_value]` does not support generic structs",
+        ));
+    }
+    if matches!(item.fields, Fields::Unit) {
+        return Err(syn::Error::new(
+            item.ident.span(),
+            "`#[mapping_value]` needs at least one field",
+        ));
+    }
+
+    let ident = &item.ident;
+    let type_name = ident.to_string();
+    let mapping_name = LitByteStr::new(name.value().as_bytes(), name.span());
+
+    let field_types = item
+        .fields
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+    let pod_checks = field_types.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            __assert_pod::<#ty>();
+        }
+    });
+    let padding_message = LitStr::new(
+        &format!(
+            "`{type_name}` has padding bytes and cannot be Pod: reorder the fields, \
+             use alignment-1 fields (e.g. `PodU64`, `[u8; 8]`) or add explicit padding fields"
+        ),
+        ident.span(),
+    );
+
+    Ok(quote! {
+        #[repr(C)]
+        #[derive(Clone, Copy, ::bytemuck::Pod, ::bytemuck::Zeroable)]
+        #item
+
+        const _: fn() = || {
+            fn __assert_pod<T: ::bytemuck::Pod>() {}
+            #(#pod_checks)*
+        };
+
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<#ident>() == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+            #padding_message
+        );
+
+        impl ::pda_pinocchio_mapping::MappingValue for #ident {
+            const DISCRIMINATOR: [u8; 8] = ::pda_pinocchio_mapping::discriminator(#type_name);
+            const VERSION: u8 = #version;
+        }
+
+        impl #ident {
+            pub const MAPPING_NAME: &'static [u8] = #mapping_name;
+
+            /// Mapping of this value type under `MAPPING_NAME`, whose entries
+            /// start with a header holding `DISCRIMINATOR` and `VERSION`.
+            pub fn mapping<'a>(
+                program_id: &'a ::pinocchio::pubkey::Pubkey,
+                payer: &'a ::pinocchio::account_info::AccountInfo,
+            ) -> ::pda_pinocchio_mapping::Mapping<'a, #ident> {
+                ::pda_pinocchio_mapping::Mapping::new(program_id, Self::MAPPING_NAME, payer)
+                    .with_header()
+            }
+        }
+    })
+}
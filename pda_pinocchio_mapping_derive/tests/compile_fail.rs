@@ -0,0 +1,8 @@
+/// Checks the errors reported by `#[derive(Bumpy)]` and `#[mapping_value]`
+/// on invalid structs. Regenerate the `.stderr` files with
+/// `TRYBUILD=overwrite cargo test -p pda_pinocchio_mapping_derive`.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
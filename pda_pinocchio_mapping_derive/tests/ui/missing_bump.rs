@@ -0,0 +1,9 @@
+use pda_pinocchio_mapping::Bumpy;
+
+#[derive(Bumpy)]
+pub struct Share {
+    pub amount: [u8; 8],
+    pub share_bump: u8,
+}
+
+fn main() {}
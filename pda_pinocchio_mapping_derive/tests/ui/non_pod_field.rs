@@ -0,0 +1,9 @@
+use pda_pinocchio_mapping::mapping_value;
+
+#[mapping_value(name = "shares")]
+pub struct Share {
+    pub active: bool,
+    pub bump: u8,
+}
+
+fn main() {}